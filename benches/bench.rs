@@ -4,13 +4,27 @@ extern crate branka;
 extern crate branca;
 
 use branca::Branca;
+use branka::internals::{base62_decode, base62_encode};
 use branka::Branka;
 
 use criterion::*;
 
 use getrandom::getrandom;
 
-static INPUT_SIZES: [usize; 5] = [128, 256, 512, 1024, 2 * 1024];
+static INPUT_SIZES: [usize; 6] = [64, 256, 1024, 4 * 1024, 16 * 1024, 64 * 1024];
+
+// Base62 is quadratic in the token length: past this size a single iteration
+// takes long enough that the default sample count would run for minutes.
+const LARGE_INPUT: usize = 4 * 1024;
+
+fn configure(group: &mut BenchmarkGroup<measurement::WallTime>, size: usize) {
+    group.throughput(Throughput::Bytes(size as u64));
+    if size > LARGE_INPUT {
+        group.sample_size(10);
+    } else {
+        group.sample_size(100);
+    }
+}
 
 mod random_tokens {
     use super::*;
@@ -25,8 +39,7 @@ mod random_tokens {
             let mut input = vec![0u8; *size];
             getrandom(&mut input).unwrap();
 
-            // group.throughput(Throughput::Bytes(*size as u64));
-            group.throughput(Throughput::Elements(1));
+            configure(&mut group, *size);
             let branca = Branka::new(&key, 3000);
             group.bench_with_input(
                 BenchmarkId::new("branka", *size),
@@ -59,23 +72,22 @@ mod random_tokens {
         let mut group = c.benchmark_group("decode");
         let mut key = [0u8; 32];
         getrandom(&mut key).unwrap();
-        
+
 
         for size in INPUT_SIZES.iter() {
             let mut input = vec![0u8; *size];
             getrandom(&mut input).unwrap();
             let branca_ = Branka::new(&key, 3000);
             let token = branca_.encode(&input);
-            let branca = Branka::new(&key, 3000);
+            let branca = Branka::new(&key, 3000).with_max_token_len(token.len());
 
-            // group.throughput(Throughput::Bytes(*size as u64));
-            group.throughput(Throughput::Elements(1));
+            configure(&mut group, *size);
             group.bench_with_input(
                 BenchmarkId::new("branka", *size),
                 &token,
                 |b, input_message| {
                     b.iter(|| {
-                        let r = branca.decode(&input_message).unwrap();
+                        let r = branca.decode(input_message).unwrap();
                         black_box(r);
                     })
                 },
@@ -97,12 +109,40 @@ mod random_tokens {
         }
     }
 
+    // The text encoding layer alone, to split token cost between crypto and base62.
+    pub fn bench_base62(c: &mut Criterion) {
+        let mut group = c.benchmark_group("base62");
+
+        for size in INPUT_SIZES.iter() {
+            let mut input = vec![0u8; *size];
+            getrandom(&mut input).unwrap();
+            let encoded = base62_encode(&input);
+
+            configure(&mut group, *size);
+            group.bench_with_input(
+                BenchmarkId::new("encode", *size),
+                &input,
+                |b, input| {
+                    b.iter(|| black_box(base62_encode(input)))
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new("decode", *size),
+                &encoded,
+                |b, encoded| {
+                    b.iter(|| black_box(base62_decode(encoded).unwrap()))
+                },
+            );
+        }
+    }
+
     criterion_group! {
         name = random_tokens;
         config = Criterion::default();
         targets =
         bench_encode,
         bench_decode,
+        bench_base62,
     }
 }
 
@@ -0,0 +1,19 @@
+#![no_main]
+
+use branka::internals::{base62_decode, base62_encode};
+use libfuzzer_sys::fuzz_target;
+
+const BASE62: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// Fuzz the crate's own base62 codec, checking it against `base_x`: arbitrary
+// bytes must round trip to the same text, and arbitrary text must decode
+// (or fail) exactly as with `base_x`, without panicking.
+fuzz_target!(|data: &[u8]| {
+    let encoded = base62_encode(data);
+    assert_eq!(encoded, base_x::encode(BASE62, data));
+    assert_eq!(base62_decode(&encoded).unwrap(), data);
+
+    if let Ok(text) = core::str::from_utf8(data) {
+        assert_eq!(base62_decode(text), base_x::decode(BASE62, text).ok());
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Anything accepted by from_bearer must survive a round trip.
+fuzz_target!(|data: &str| {
+    if let Ok(token) = branka::from_bearer(data) {
+        assert_eq!(branka::from_bearer(&branka::to_bearer(token)).unwrap(), token);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use branka::Branka;
+use libfuzzer_sys::fuzz_target;
+
+// Attacker-controlled tokens must never panic, whatever the failure.
+fuzz_target!(|data: &str| {
+    let branka = Branka::new(&[0x42; 32], 0);
+    let _ = branka.decode(data);
+    let _ = branka.decode_with_report(data);
+});
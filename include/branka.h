@@ -0,0 +1,85 @@
+#ifndef BRANKA_H
+#define BRANKA_H
+
+/* Generated with cbindgen. Do not edit by hand. */
+
+#include <stdint.h>
+#include <stddef.h>
+
+#define BRANKA_OK 0
+
+#define BRANKA_ERR_NULL -1
+
+#define BRANKA_ERR_INVALID_TOKEN -2
+
+#define BRANKA_ERR_EXPIRED -3
+
+#define BRANKA_ERR_RNG -4
+
+typedef struct Branka Branka;
+
+/**
+ * Create a Branka instance from a 32 bytes key.
+ * Returns NULL if `key` is NULL or `key_len` is not 32.
+ * The instance must be released with branka_free.
+ *
+ * # Safety
+ * `key` must point to `key_len` readable bytes.
+ */
+Branka *branka_new(const uint8_t *key,
+                   size_t key_len,
+                   uint32_t ttl);
+
+/**
+ * Release an instance created by branka_new. NULL is ignored.
+ *
+ * # Safety
+ * `branka` must come from branka_new and not be used afterwards.
+ */
+void branka_free(Branka *branka);
+
+/**
+ * Encode `data` into a NUL-terminated token written to `*token`.
+ * The token must be released with branka_string_free.
+ *
+ * # Safety
+ * `branka` must come from branka_new, `data` must point to `data_len`
+ * readable bytes (it may be NULL when `data_len` is 0) and `token` must be a
+ * valid pointer.
+ */
+int branka_encode(const Branka *branka,
+                  const uint8_t *data,
+                  size_t data_len,
+                  char **token);
+
+/**
+ * Decode a NUL-terminated token. On success the payload is written to
+ * `*data`/`*data_len` and must be released with branka_bytes_free.
+ *
+ * # Safety
+ * `branka` must come from branka_new, `token` must be a NUL-terminated string
+ * and `data`/`data_len` must be valid pointers.
+ */
+int branka_decode(const Branka *branka,
+                  const char *token,
+                  uint8_t **data,
+                  size_t *data_len);
+
+/**
+ * Release a token returned by branka_encode. NULL is ignored.
+ *
+ * # Safety
+ * `token` must come from branka_encode and not be used afterwards.
+ */
+void branka_string_free(char *token);
+
+/**
+ * Release a payload returned by branka_decode. NULL is ignored.
+ *
+ * # Safety
+ * `data`/`data_len` must come from branka_decode and not be used afterwards.
+ */
+void branka_bytes_free(uint8_t *data,
+                       size_t data_len);
+
+#endif /* BRANKA_H */
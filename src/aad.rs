@@ -0,0 +1,42 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Branka, BrankaError};
+
+impl Branka {
+    // Like try_encode, but also authenticates `aad`: context the token is
+    // bound to (a request path, a tenant id, ...) that is not stored in it.
+    // The token only decodes with decode_aad and the same `aad`.
+    pub fn encode_aad(&self, data: &[u8], aad: &[u8]) -> Result<String, BrankaError> {
+        let nonce = self.next_nonce()?;
+        Ok(self.encode_with_nonce(&nonce, self.now(), data, aad))
+    }
+
+    // Decode a token from encode_aad. A different `aad` fails like a
+    // tampered token, with InvalidData.
+    pub fn decode_aad(&self, data: &str, aad: &[u8]) -> Result<Vec<u8>, BrankaError> {
+        self.decode_full_aad(data, aad)
+            .map(|decoded| decoded.payload)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aad_binding() {
+        let branka = Branka::new(&[3u8; 32], 0);
+        let token = branka.encode_aad(b"payload", b"tenant:1").unwrap();
+        assert_eq!(branka.decode_aad(&token, b"tenant:1").unwrap(), b"payload");
+        assert_eq!(
+            branka.decode_aad(&token, b"tenant:2"),
+            Err(BrankaError::InvalidData)
+        );
+        assert_eq!(branka.decode(&token), Err(BrankaError::InvalidData));
+
+        // Without context, the token is a plain branka token.
+        let token = branka.encode_aad(b"payload", b"").unwrap();
+        assert_eq!(branka.decode(&token).unwrap(), b"payload");
+    }
+}
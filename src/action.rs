@@ -0,0 +1,103 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Branka, BrankaError, UsedTokenStore};
+
+pub const EMAIL_VERIFICATION: &str = "email-verification";
+pub const PASSWORD_RESET: &str = "password-reset";
+
+// Time to live of the tokens made by the convenience constructors.
+pub const EMAIL_VERIFICATION_TTL: u32 = 24 * 3600;
+pub const PASSWORD_RESET_TTL: u32 = 15 * 60;
+
+// Single-use tokens authorizing one action for one user: the links in email
+// verification and password reset mails. The purpose is sealed into the
+// token, so a token minted for one action is rejected by the verifier of
+// another even under the same key.
+pub struct ActionTokens<S> {
+    branka: Branka,
+    store: S,
+    purpose: &'static str,
+}
+
+impl<S: UsedTokenStore> ActionTokens<S> {
+    // The TTL of `branka` bounds how long tokens stay valid: keep it short.
+    pub fn new(branka: Branka, store: S, purpose: &'static str) -> Self {
+        ActionTokens {
+            branka,
+            store,
+            purpose,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn email_verification(key: &[u8], store: S) -> Self {
+        ActionTokens::new(
+            Branka::new(key, EMAIL_VERIFICATION_TTL),
+            store,
+            EMAIL_VERIFICATION,
+        )
+    }
+
+    #[cfg(feature = "std")]
+    pub fn password_reset(key: &[u8], store: S) -> Self {
+        ActionTokens::new(Branka::new(key, PASSWORD_RESET_TTL), store, PASSWORD_RESET)
+    }
+
+    pub fn issue(&self, user_id: &str) -> String {
+        self.issue_bytes(user_id.as_bytes())
+    }
+
+    // Verify the token and consume it, returning the user id.
+    // Tokens for another purpose fail with WrongTokenKind without being
+    // consumed; a second use fails with TokenReused.
+    pub fn verify(&self, token: &str) -> Result<String, BrankaError> {
+        String::from_utf8(self.verify_bytes(token)?).map_err(|_| BrankaError::InvalidPayload)
+    }
+
+    pub(crate) fn issue_bytes(&self, data: &[u8]) -> String {
+        // Purpose || 0 || Data
+        let mut payload = Vec::with_capacity(self.purpose.len() + 1 + data.len());
+        payload.extend_from_slice(self.purpose.as_bytes());
+        payload.push(0);
+        payload.extend_from_slice(data);
+        self.branka.encode(&payload)
+    }
+
+    pub(crate) fn verify_bytes(&self, token: &str) -> Result<Vec<u8>, BrankaError> {
+        let decoded = self.branka.decode_full(token)?;
+        let data = decoded
+            .payload
+            .strip_prefix(self.purpose.as_bytes())
+            .and_then(|rest| rest.strip_prefix(&[0]))
+            .ok_or(BrankaError::WrongTokenKind)?
+            .to_vec();
+
+        let expires_at = self.branka.expires_at(decoded.timestamp);
+        if !self
+            .store
+            .mark_used(&decoded.nonce, expires_at, self.branka.now())
+        {
+            return Err(BrankaError::TokenReused);
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::MemoryUsedTokenStore;
+
+    #[test]
+    fn test_action_tokens() {
+        let key = [2u8; 32];
+        let reset = ActionTokens::password_reset(&key, MemoryUsedTokenStore::new());
+        let verify = ActionTokens::email_verification(&key, MemoryUsedTokenStore::new());
+
+        let token = reset.issue("user:42");
+        assert_eq!(verify.verify(&token), Err(BrankaError::WrongTokenKind));
+        assert_eq!(reset.verify(&token).unwrap(), "user:42");
+        assert_eq!(reset.verify(&token), Err(BrankaError::TokenReused));
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Branka, BrankaError};
+
+// An API key: `<prefix>_<token>`, e.g. `bk_live_4SvYKmVs...`. The prefix
+// says what the key is for at a glance (and to secret scanners); the token
+// is the secret part.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    key: String,
+    prefix_len: usize,
+}
+
+impl ApiKey {
+    // The full key, to hand to the client once.
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    pub fn into_string(self) -> String {
+        self.key
+    }
+
+    // The non-secret prefix, without the trailing `_`.
+    pub fn prefix(&self) -> &str {
+        &self.key[..self.prefix_len]
+    }
+
+    // A form safe to show in dashboards and logs: the prefix and the last
+    // four characters, e.g. `bk_live_…Wx3d`.
+    pub fn redacted(&self) -> String {
+        let tail = &self.key[self.key.len() - 4..];
+        let mut redacted = String::with_capacity(self.prefix_len + 8);
+        redacted.push_str(self.prefix());
+        redacted.push_str("_…");
+        redacted.push_str(tail);
+        redacted
+    }
+}
+
+// Never print the secret part.
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ApiKey").field(&self.redacted()).finish()
+    }
+}
+
+// Mints and verifies API keys under one prefix. The prefix is bound to the
+// token as associated data, so a key cannot be passed off under another
+// prefix (a `test` key as a `live` one).
+pub struct ApiKeys {
+    branka: Branka,
+    prefix: String,
+}
+
+impl ApiKeys {
+    // prefix: ASCII letters, digits and `_`, e.g. `bk_live`.
+    // API keys are long-lived: `branka` usually has a TTL of 0.
+    pub fn new(branka: Branka, prefix: &str) -> ApiKeys {
+        assert!(
+            !prefix.is_empty()
+                && prefix
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_'),
+            "invalid API key prefix"
+        );
+        ApiKeys {
+            branka,
+            prefix: String::from(prefix),
+        }
+    }
+
+    // Mint a key carrying `data` (an account id, scopes, ...).
+    pub fn issue(&self, data: &[u8]) -> Result<ApiKey, BrankaError> {
+        let token = self.branka.encode_aad(data, self.prefix.as_bytes())?;
+        let mut key = String::with_capacity(self.prefix.len() + 1 + token.len());
+        key.push_str(&self.prefix);
+        key.push('_');
+        key.push_str(&token);
+        Ok(ApiKey {
+            key,
+            prefix_len: self.prefix.len(),
+        })
+    }
+
+    // Verify a key and return its data. Keys with another prefix fail with
+    // WrongTokenKind.
+    pub fn verify(&self, key: &str) -> Result<Vec<u8>, BrankaError> {
+        let token = key
+            .strip_prefix(self.prefix.as_str())
+            .and_then(|rest| rest.strip_prefix('_'))
+            .ok_or(BrankaError::WrongTokenKind)?;
+        self.branka.decode_aad(token, self.prefix.as_bytes())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_keys() {
+        let key = [6u8; 32];
+        let live = ApiKeys::new(Branka::new(&key, 0), "bk_live");
+        let test = ApiKeys::new(Branka::new(&key, 0), "bk_test");
+
+        let api_key = live.issue(b"acct:1").unwrap();
+        assert!(api_key.as_str().starts_with("bk_live_"));
+        assert_eq!(api_key.prefix(), "bk_live");
+        assert!(api_key.redacted().starts_with("bk_live_…"));
+        assert_eq!(live.verify(api_key.as_str()).unwrap(), b"acct:1");
+        assert_eq!(
+            test.verify(api_key.as_str()),
+            Err(BrankaError::WrongTokenKind)
+        );
+
+        let relabeled = api_key.as_str().replacen("bk_live", "bk_test", 1);
+        assert_eq!(test.verify(&relabeled), Err(BrankaError::InvalidData));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::BrankaError;
+
+// Non-sensitive metadata about an encoded or decoded token.
+#[derive(Debug, Clone, Copy)]
+pub struct AuditEvent {
+    // Issue timestamp of the token.
+    pub timestamp: u32,
+    // Plaintext payload length in bytes.
+    pub payload_len: usize,
+}
+
+// Receives a callback for every token processed by a Branka instance, for
+// centralized security logging. Never sees payloads or key material.
+pub trait AuditSink: Send + Sync {
+    fn on_encoded(&self, _event: &AuditEvent) {}
+    fn on_decoded(&self, _event: &AuditEvent) {}
+    fn on_rejected(&self, _reason: &BrankaError) {}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Branka;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl AuditSink for Recorder {
+        fn on_encoded(&self, event: &AuditEvent) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("encoded {}", event.payload_len));
+        }
+
+        fn on_decoded(&self, event: &AuditEvent) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("decoded {}", event.payload_len));
+        }
+
+        fn on_rejected(&self, reason: &BrankaError) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("rejected {:?}", reason));
+        }
+    }
+
+    #[test]
+    fn test_audit_sink_callbacks() {
+        let recorder = Arc::new(Recorder::default());
+        let branka = Branka::new(&[6u8; 32], 0).with_audit_sink(recorder.clone());
+
+        let token = branka.encode(b"hello");
+        branka.decode(&token).unwrap();
+        assert!(branka.decode("not-base62!").is_err());
+
+        let token = branka.encode_v2(b"v2", &crate::Extensions::new()).unwrap();
+        branka.decode_v2(&token).unwrap();
+        assert!(branka.decode_v2("0").is_err());
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![
+                "encoded 5",
+                "decoded 5",
+                "rejected InvalidBase62",
+                "encoded 2",
+                "decoded 2",
+                "rejected InvalidDataLength"
+            ]
+        );
+    }
+}
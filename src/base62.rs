@@ -0,0 +1,157 @@
+// Base62 without allocating: produces the same text as `base_x` with the
+// BASE62 alphabet, but works in caller-provided buffers.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+// Largest power of 62 fitting in a u32: each pass over the input divides it
+// by BIG_BASE and yields five digits.
+const BIG_BASE: u64 = 62 * 62 * 62 * 62 * 62;
+const BIG_DIGITS: usize = 5;
+
+// Digit value of each ASCII character, or INVALID.
+const INVALID: u8 = 0xFF;
+const DIGITS: [u8; 256] = {
+    let mut digits = [INVALID; 256];
+    let mut i = 0;
+    while i < ALPHABET.len() {
+        digits[ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+    digits
+};
+
+// Encode `input` into `out`, replacing its contents. `input` is used as the
+// working number and is left zeroed.
+pub(crate) fn encode_into(input: &mut [u8], out: &mut String) {
+    out.clear();
+    if input.is_empty() {
+        return;
+    }
+    out.reserve(input.len() * 4 / 3 + 1);
+    // Every leading zero byte but the last becomes a leading '0'.
+    let leaders = input[..input.len() - 1]
+        .iter()
+        .take_while(|byte| **byte == 0)
+        .count();
+
+    // Digits come out least significant first, and are reversed at the end.
+    let mut start = 0;
+    loop {
+        // Long division, four bytes at a time once the leading bytes are
+        // out of the way.
+        let mut rem = 0u64;
+        let number = &mut input[start..];
+        let (head, tail) = number.split_at_mut(number.len() % 4);
+        for byte in head {
+            let acc = rem << 8 | u64::from(*byte);
+            *byte = (acc / BIG_BASE) as u8;
+            rem = acc % BIG_BASE;
+        }
+        for limb in tail.chunks_exact_mut(4) {
+            let acc = rem << 32 | u64::from(u32::from_be_bytes(limb.try_into().unwrap()));
+            limb.copy_from_slice(&((acc / BIG_BASE) as u32).to_be_bytes());
+            rem = acc % BIG_BASE;
+        }
+        while start < input.len() && input[start] == 0 {
+            start += 1;
+        }
+
+        if start == input.len() {
+            loop {
+                out.push(ALPHABET[(rem % 62) as usize] as char);
+                rem /= 62;
+                if rem == 0 {
+                    break;
+                }
+            }
+            break;
+        }
+        for _ in 0..BIG_DIGITS {
+            out.push(ALPHABET[(rem % 62) as usize] as char);
+            rem /= 62;
+        }
+    }
+    out.extend(core::iter::repeat_n('0', leaders));
+
+    // All ASCII: reversing the bytes keeps the string valid UTF-8.
+    let mut bytes = core::mem::take(out).into_bytes();
+    bytes.reverse();
+    *out = String::from_utf8(bytes).unwrap();
+}
+
+// Decode `input` into `out`, replacing its contents.
+pub(crate) fn decode_into(input: &str, out: &mut Vec<u8>) -> Result<(), ()> {
+    out.clear();
+    if input.is_empty() {
+        return Ok(());
+    }
+    out.reserve(input.len() * 3 / 4 + 4);
+
+    // The number is built in little-endian u32 limbs, five digits at a time,
+    // and reversed to big-endian at the end.
+    for group in input.as_bytes().chunks(BIG_DIGITS) {
+        let mut value = 0u64;
+        let mut scale = 1u64;
+        for c in group {
+            let digit = DIGITS[*c as usize];
+            if digit == INVALID {
+                return Err(());
+            }
+            value = value * 62 + u64::from(digit);
+            scale *= 62;
+        }
+
+        let mut carry = value;
+        for limb in out.chunks_exact_mut(4) {
+            let acc = u64::from(u32::from_le_bytes(limb.try_into().unwrap())) * scale + carry;
+            limb.copy_from_slice(&(acc as u32).to_le_bytes());
+            carry = acc >> 32;
+        }
+        if carry > 0 {
+            out.extend_from_slice(&(carry as u32).to_le_bytes());
+        }
+    }
+
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    // Each leading '0' stands for a leading zero byte.
+    let leaders = input.bytes().take_while(|c| *c == b'0').count();
+    out.extend(core::iter::repeat_n(0, leaders));
+    out.reverse();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BASE62;
+    use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+
+    #[test]
+    fn test_matches_base_x() {
+        let mut cases: Vec<Vec<u8>> = vec![vec![], vec![0], vec![0, 0], vec![0, 5], vec![255; 3]];
+        for len in 1..200 {
+            let mut input = vec![0u8; len];
+            OsRng.fill_bytes(&mut input);
+            input[0] = 0;
+            cases.push(input[1..].to_vec());
+            cases.push(input);
+        }
+
+        let (mut text, mut bytes) = (String::new(), Vec::new());
+        for input in cases {
+            encode_into(&mut input.clone(), &mut text);
+            assert_eq!(text, base_x::encode(BASE62, &input), "{:?}", input);
+            decode_into(&text, &mut bytes).unwrap();
+            assert_eq!(bytes, input);
+        }
+        for text in ["0", "00", "01", "zz", "10000000000"] {
+            decode_into(text, &mut bytes).unwrap();
+            assert_eq!(bytes, base_x::decode(BASE62, text).unwrap(), "{}", text);
+        }
+        assert!(decode_into("ab-c", &mut bytes).is_err());
+    }
+}
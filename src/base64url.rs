@@ -0,0 +1,81 @@
+// Unpadded base64url (RFC 4648 §5), for the compact token profile.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub(crate) fn encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let mut group = [0u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from(group[0]) << 16 | u32::from(group[1]) << 8 | u32::from(group[2]);
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+// Rejects padding, characters outside the alphabet and non-canonical input
+// (unused trailing bits set), so each byte string has exactly one encoding.
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>, ()> {
+    let mut out = Vec::new();
+    decode_into(input, &mut out)?;
+    Ok(out)
+}
+
+// Like decode, but appends to `out`, which is grown at most once.
+pub(crate) fn decode_into(input: &str, out: &mut Vec<u8>) -> Result<(), ()> {
+    if input.len() % 4 == 1 {
+        return Err(());
+    }
+    out.reserve(input.len() / 4 * 3 + 2);
+    for chunk in input.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'-' => 62,
+                b'_' => 63,
+                _ => return Err(()),
+            };
+            bits |= u32::from(value) << (18 - 6 * i);
+        }
+        let bytes = bits.to_be_bytes();
+        let len = chunk.len() - 1;
+        if bytes[1 + len..].iter().any(|&b| b != 0) {
+            return Err(());
+        }
+        out.extend_from_slice(&bytes[1..1 + len]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64url_rfc4648_vectors() {
+        for (raw, encoded) in [
+            ("", ""),
+            ("f", "Zg"),
+            ("fo", "Zm8"),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg"),
+            ("fooba", "Zm9vYmE"),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(raw.as_bytes()), encoded);
+            assert_eq!(decode(encoded).unwrap(), raw.as_bytes());
+        }
+        assert_eq!(encode(&[0xfb, 0xff]), "-_8");
+        assert!(decode("Zh").is_err());
+        assert!(decode("Zg==").is_err());
+        assert!(decode("Z").is_err());
+    }
+}
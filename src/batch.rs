@@ -0,0 +1,89 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(not(feature = "rayon"))]
+use std::thread;
+
+use crate::{Branka, BrankaError};
+
+// Below this many items per thread, spreading the work costs more than it
+// saves.
+const MIN_ITEMS_PER_THREAD: usize = 64;
+
+impl Branka {
+    // Encode every item, spreading the work over the rayon thread pool (with
+    // the `rayon` feature) or one scoped thread per core. Tokens are returned
+    // in the order of `items`.
+    pub fn encode_batch<T: AsRef<[u8]> + Sync>(&self, items: &[T]) -> Vec<String> {
+        self.batch(items, |item| self.encode(item.as_ref()))
+    }
+
+    // Decode every token, spreading the work over the available cores.
+    // One result per token, in the order of `tokens`.
+    pub fn decode_batch<T: AsRef<str> + Sync>(
+        &self,
+        tokens: &[T],
+    ) -> Vec<Result<Vec<u8>, BrankaError>> {
+        self.batch(tokens, |token| self.decode(token.as_ref()))
+    }
+
+    #[cfg(feature = "rayon")]
+    pub(crate) fn batch<T: Sync, R: Send>(
+        &self,
+        items: &[T],
+        f: impl Fn(&T) -> R + Sync + Send,
+    ) -> Vec<R> {
+        items
+            .par_iter()
+            .with_min_len(MIN_ITEMS_PER_THREAD)
+            .map(f)
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    pub(crate) fn batch<T: Sync, R: Send>(
+        &self,
+        items: &[T],
+        f: impl Fn(&T) -> R + Sync + Send,
+    ) -> Vec<R> {
+        let threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(items.len() / MIN_ITEMS_PER_THREAD);
+        if threads <= 1 {
+            return items.iter().map(f).collect();
+        }
+
+        let chunk_size = items.len().div_ceil(threads);
+        let f = &f;
+        thread::scope(|scope| {
+            let workers: Vec<_> = items
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_keeps_order() {
+        let branka = Branka::new(&[4u8; 32], 0);
+        let items: Vec<Vec<u8>> = (0..1000u32).map(|i| i.to_be_bytes().to_vec()).collect();
+
+        let mut tokens = branka.encode_batch(&items);
+        tokens[7] = "garbage".to_string();
+        let decoded = branka.decode_batch(&tokens);
+        assert_eq!(decoded.len(), items.len());
+        assert!(decoded[7].is_err());
+        for (i, result) in decoded.iter().enumerate().filter(|(i, _)| *i != 7) {
+            assert_eq!(result.as_ref().unwrap(), &items[i]);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::string::String;
+
+use crate::BrankaError;
+
+// Authentication scheme used in `Authorization` headers (RFC 6750).
+const SCHEME: &str = "Bearer";
+
+// Format a token as an `Authorization` header value: `Bearer <token>`.
+pub fn to_bearer(token: &str) -> String {
+    let mut value = String::with_capacity(SCHEME.len() + 1 + token.len());
+    value.push_str(SCHEME);
+    value.push(' ');
+    value.push_str(token);
+    value
+}
+
+// Extract the token from an `Authorization` header value.
+// The scheme is matched case-insensitively, surrounding whitespace is ignored
+// and the token itself must be a non-empty base62 string.
+pub fn from_bearer(value: &str) -> Result<&str, BrankaError> {
+    let value = value.trim_matches(|c| c == ' ' || c == '\t');
+    let (scheme, token) = value
+        .split_once(' ')
+        .ok_or(BrankaError::InvalidAuthorization)?;
+    if !scheme.eq_ignore_ascii_case(SCHEME) {
+        return Err(BrankaError::InvalidAuthorization);
+    }
+
+    let token = token.trim_start_matches(' ');
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(BrankaError::InvalidAuthorization);
+    }
+
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bearer_round_trip() {
+        let value = to_bearer("4SvYKmVsNHrEbWwG2Bv");
+        assert_eq!(value, "Bearer 4SvYKmVsNHrEbWwG2Bv");
+        assert_eq!(from_bearer(&value).unwrap(), "4SvYKmVsNHrEbWwG2Bv");
+    }
+
+    #[test]
+    fn test_bearer_edge_cases() {
+        assert_eq!(from_bearer("bearer abc").unwrap(), "abc");
+        assert_eq!(from_bearer("  BEARER   abc\t").unwrap(), "abc");
+
+        assert!(from_bearer("Bearer").is_err());
+        assert!(from_bearer("Bearer ").is_err());
+        assert!(from_bearer("Basic abc").is_err());
+        assert!(from_bearer("Bearerabc").is_err());
+        assert!(from_bearer("Bearer abc def").is_err());
+        assert!(from_bearer("Bearer\tabc").is_err());
+        assert!(from_bearer("Bearer abc=").is_err());
+    }
+}
@@ -0,0 +1,134 @@
+// Defaults from `~/.config/branka/config.toml`, overridden by command line flags.
+//
+// Only flat `name = value` lines are understood, with string or integer values:
+//
+//     key_env = "BRANKA_STAGING_KEY"   # or key_file / keyring = "path"
+//     ttl = 3600
+//     format = "base64"
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{parse_format, Options};
+
+pub fn load() -> Result<Options, String> {
+    let mut options = Options::default();
+    let path = match config_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(options),
+    };
+    let contents =
+        fs::read_to_string(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    for (i, line) in contents.lines().enumerate() {
+        let at = |msg: &str| format!("{}:{}: {}", path.display(), i + 1, msg);
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| at("expected `name = value`"))?;
+        let value = value.trim();
+        match name.trim() {
+            "key_env" => {
+                options.key_env = Some(string(value).ok_or_else(|| at("expected a string"))?)
+            }
+            "key_file" => {
+                options.key_file = Some(relative_to(
+                    dir,
+                    &string(value).ok_or_else(|| at("expected a string"))?,
+                ))
+            }
+            "keyring" => {
+                options.keyring = Some(relative_to(
+                    dir,
+                    &string(value).ok_or_else(|| at("expected a string"))?,
+                ))
+            }
+            "ttl" => {
+                options.ttl = value
+                    .parse()
+                    .map_err(|_| at("ttl must be a number of seconds"))?
+            }
+            "format" => {
+                let format = string(value).ok_or_else(|| at("expected a string"))?;
+                options.format = Some(parse_format(&format).map_err(|err| at(&err))?);
+            }
+            other => return Err(at(&format!("unknown setting {:?}", other))),
+        }
+    }
+    Ok(options)
+}
+
+// $BRANKA_CONFIG, else $XDG_CONFIG_HOME/branka/config.toml, else ~/.config/branka/config.toml.
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("BRANKA_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("branka").join("config.toml"))
+}
+
+// A `#` starts a comment unless it is inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '\\' if quoted && !escaped => {
+                escaped = true;
+                continue;
+            }
+            '"' if !escaped => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+// A basic TOML string: double quoted, with \" and \\ escapes.
+fn string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                c @ ('"' | '\\') => out.push(c),
+                _ => return None,
+            },
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+// Paths in the config file are relative to the file itself.
+fn relative_to(dir: &Path, path: &str) -> String {
+    dir.join(path).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_values() {
+        assert_eq!(string(r#""a \"b\" \\ c""#).as_deref(), Some(r#"a "b" \ c"#));
+        assert_eq!(string("bare"), None);
+        assert_eq!(string(r#""a" "b""#), None);
+        assert_eq!(
+            strip_comment(r#"key_file = "a#b" # note"#),
+            r#"key_file = "a#b" "#
+        );
+    }
+}
@@ -0,0 +1,110 @@
+// Text encodings for keys and payloads.
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn decode_hex(text: &str) -> Result<Vec<u8>, String> {
+    if !text.len().is_multiple_of(2) {
+        return Err("invalid hex: odd length".to_string());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| {
+            text.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| "invalid hex".to_string())
+        })
+        .collect()
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Standard alphabet, padded.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+pub fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim_end_matches('=');
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut n = 0u32;
+    for (i, c) in text.bytes().enumerate() {
+        let value = BASE64
+            .iter()
+            .position(|&b| b == c)
+            .ok_or_else(|| "invalid base64".to_string())?;
+        n = n << 6 | value as u32;
+        if i % 4 == 3 {
+            out.extend_from_slice(&n.to_be_bytes()[1..]);
+            n = 0;
+        }
+    }
+    match text.len() % 4 {
+        0 => {}
+        2 => out.push((n >> 4) as u8),
+        3 => out.extend_from_slice(&((n >> 2) as u16).to_be_bytes()),
+        _ => return Err("invalid base64: bad length".to_string()),
+    }
+    Ok(out)
+}
+
+// JSON payloads are carried compact: parsing and reserializing checks the
+// input and drops its whitespace.
+pub fn decode_json(text: &str) -> Result<Vec<u8>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|err| format!("invalid JSON: {}", err))?;
+    Ok(value.to_string().into_bytes())
+}
+
+pub fn encode_json(payload: &[u8]) -> Result<String, String> {
+    let value: serde_json::Value =
+        serde_json::from_slice(payload).map_err(|err| format!("payload is not JSON: {}", err))?;
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        for (raw, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xff\xfe\x00", "//4A"),
+        ] {
+            assert_eq!(encode_base64(raw), encoded);
+            assert_eq!(decode_base64(encoded).unwrap(), raw);
+        }
+    }
+
+    #[test]
+    fn test_json_compacts() {
+        let payload = decode_json("{ \"sub\": \"user-1\",\n  \"admin\": true }\n").unwrap();
+        assert_eq!(payload, br#"{"admin":true,"sub":"user-1"}"#);
+        assert_eq!(
+            encode_json(&payload).unwrap(),
+            r#"{"admin":true,"sub":"user-1"}"#
+        );
+        assert!(decode_json("{").is_err());
+        assert!(encode_json(b"\xff").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+// `branka inspect`: what is in a token, and why it may not decode.
+
+use std::env;
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use branka::internals::{base62_decode, base64url_decode};
+
+use super::{encode_base64, encode_hex, encode_json, load_keyring, Format, Options};
+
+const TAG_LEN: usize = 16;
+
+// Where the header fields of each token version sit.
+struct Layout {
+    version: u8,
+    name: &'static str,
+    // Compact tokens are base64url, the others base62.
+    compact: bool,
+    timestamp: bool,
+    nonce: Range<usize>,
+    // Fixed header length; v2 tokens add their extensions after it.
+    header_len: usize,
+}
+
+const LAYOUTS: [Layout; 4] = [
+    Layout {
+        version: 0xBA,
+        name: "branca",
+        compact: false,
+        timestamp: true,
+        nonce: 5..29,
+        header_len: 29,
+    },
+    Layout {
+        version: 0xBB,
+        name: "v2, with extensions",
+        compact: false,
+        timestamp: true,
+        nonce: 5..29,
+        header_len: 31,
+    },
+    Layout {
+        version: 0xBC,
+        name: "compact",
+        compact: true,
+        timestamp: true,
+        nonce: 5..17,
+        header_len: 17,
+    },
+    Layout {
+        version: 0xBD,
+        name: "compact, untimed",
+        compact: true,
+        timestamp: false,
+        nonce: 1..13,
+        header_len: 13,
+    },
+];
+
+pub fn inspect(options: &Options) -> Result<(), String> {
+    let token = options
+        .args
+        .first()
+        .ok_or_else(|| "inspect requires a TOKEN argument".to_string())?;
+
+    // Header fields are not authenticated until the token is decoded with
+    // the right key; they are shown as-is to help debugging.
+    let (raw, layout) = unpack(token)?;
+    println!("length:      {} chars, {} bytes", token.len(), raw.len());
+    println!("version:     0x{:02X} ({})", layout.version, layout.name);
+
+    let mut header_len = layout.header_len;
+    if layout.version == 0xBB && raw.len() >= header_len {
+        let extensions = u16::from_be_bytes([raw[29], raw[30]]) as usize;
+        println!("extensions:  {} bytes", extensions);
+        header_len += extensions;
+    }
+    if raw.len() < header_len + TAG_LEN {
+        return Err(format!(
+            "token too short: {} bytes, at least {} expected",
+            raw.len(),
+            header_len + TAG_LEN
+        ));
+    }
+
+    if layout.timestamp {
+        print_timestamp(
+            options,
+            u32::from_be_bytes([raw[1], raw[2], raw[3], raw[4]]),
+        );
+    } else {
+        println!("issued at:   none (untimed, never expires)");
+    }
+    println!("nonce:       {}", encode_hex(&raw[layout.nonce.clone()]));
+    println!("payload:     {} bytes", raw.len() - header_len - TAG_LEN);
+
+    if layout.compact {
+        println!("decrypted:   (not supported for compact tokens)");
+        return Ok(());
+    }
+    if options.key_file.is_none()
+        && options.keyring.is_none()
+        && options.key_env.is_none()
+        && env::var_os("BRANKA_KEY").is_none()
+    {
+        println!("decrypted:   (no key, pass --key-file or set BRANKA_KEY)");
+        return Ok(());
+    }
+    // Expiry is reported above; decrypt regardless of it.
+    let keyring = load_keyring(&Options {
+        ttl: 0,
+        ..options.clone()
+    })?;
+    let decoded = if layout.version == 0xBB {
+        keyring
+            .primary()
+            .decode_v2(token)
+            .map(|(payload, _)| payload)
+    } else {
+        keyring.decode(token)
+    };
+    match decoded {
+        Ok(payload) => match options.format.unwrap_or(Format::Hex) {
+            Format::Raw => println!("decrypted:   {}", String::from_utf8_lossy(&payload)),
+            Format::Hex => println!("decrypted:   {}", encode_hex(&payload)),
+            Format::Base64 => println!("decrypted:   {}", encode_base64(&payload)),
+            Format::Json => match encode_json(&payload) {
+                Ok(json) => println!("decrypted:   {}", json),
+                Err(err) => println!("decrypted:   {}", err),
+            },
+        },
+        Err(err) => println!("decrypted:   failed ({:?})", err),
+    }
+    Ok(())
+}
+
+// Standard tokens are base62, compact ones base64url; the version byte
+// tells them apart, as base62 text is also valid base64url.
+fn unpack(token: &str) -> Result<(Vec<u8>, &'static Layout), String> {
+    let [base62, base64] = [base62_decode(token), base64url_decode(token)];
+    for (raw, compact) in [(&base62, false), (&base64, true)] {
+        let Some(raw) = raw else { continue };
+        let found = LAYOUTS
+            .iter()
+            .find(|layout| layout.compact == compact && raw.first() == Some(&layout.version));
+        if let Some(layout) = found {
+            return Ok((raw.clone(), layout));
+        }
+    }
+    match base62
+        .as_deref()
+        .or(base64.as_deref())
+        .and_then(<[u8]>::first)
+    {
+        Some(version) => Err(format!("unknown token version 0x{:02X}", version)),
+        None => Err("token is not valid base62 or base64url".to_string()),
+    }
+}
+
+fn print_timestamp(options: &Options, timestamp: u32) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let age = now - timestamp as i64;
+
+    println!(
+        "issued at:   {} ({})",
+        format_time(timestamp as i64),
+        timestamp
+    );
+    println!("age:         {}s", age);
+    if options.ttl == 0 {
+        println!("expiry:      unknown (pass --ttl)");
+    } else if age > options.ttl as i64 {
+        println!("expiry:      expired {}s ago", age - options.ttl as i64);
+    } else {
+        println!("expiry:      valid for {}s", options.ttl as i64 - age);
+    }
+}
+
+// RFC 3339 UTC rendering of a unix timestamp.
+pub fn format_time(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let secs = timestamp.rem_euclid(86400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_time(123206400), "1973-11-27T00:00:00Z");
+        assert_eq!(format_time(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_time(4294967295), "2106-02-07T06:28:15Z");
+    }
+
+    #[test]
+    fn test_unpack_versions() {
+        let key = [3u8; 32];
+        let token = branka::Branka::new(&key, 0).encode(b"x");
+        assert_eq!(unpack(&token).unwrap().1.version, 0xBA);
+        let token = branka::Compact::new(&key, 0)
+            .without_timestamp()
+            .encode(b"x")
+            .unwrap();
+        assert_eq!(unpack(&token).unwrap().1.version, 0xBD);
+        assert!(unpack("zzzz").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+// `branka keys`: manage a keyring file for key rotation.
+//
+// The file holds one key per line, `<hex key> <added at, unix time>`, oldest
+// first; the last key is the primary one used for encoding.
+
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use branka::{Branka, Keyring};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+
+use super::inspect::format_time;
+use super::{decode_hex, encode_hex, write_secret_file, Options};
+
+struct Entry {
+    key: Vec<u8>,
+    added_at: u64,
+}
+
+pub fn keys(options: &Options) -> Result<(), String> {
+    let path = options
+        .keyring
+        .as_ref()
+        .ok_or_else(|| "keys requires --keyring PATH".to_string())?;
+    match options.args.first().map(String::as_str) {
+        Some("rotate") => rotate(path),
+        Some("list") => list(path),
+        Some("retire") => retire(path, options.ttl),
+        _ => Err("usage: branka keys rotate|list|retire --keyring PATH".to_string()),
+    }
+}
+
+// Keyring for encode/decode: primary key first, then older keys.
+pub fn load_keyring(path: &str, ttl: u32) -> Result<Keyring, String> {
+    let mut entries = read(path)?.into_iter().rev();
+    let primary = entries
+        .next()
+        .ok_or_else(|| format!("{}: keyring is empty", path))?;
+    Ok(entries.fold(
+        Keyring::new(Branka::new(&primary.key, ttl)),
+        |keyring, entry| keyring.with_previous(Branka::new(&entry.key, ttl)),
+    ))
+}
+
+fn rotate(path: &str) -> Result<(), String> {
+    let mut entries = match fs::metadata(path) {
+        Ok(_) => read(path)?,
+        Err(_) => Vec::new(),
+    };
+    let mut key = vec![0u8; 32];
+    OsRng
+        .try_fill_bytes(&mut key)
+        .map_err(|err| format!("generating key: {}", err))?;
+    println!("new primary key {}", check_value(&key));
+    entries.push(Entry {
+        key,
+        added_at: now(),
+    });
+    write(path, &entries)
+}
+
+fn list(path: &str) -> Result<(), String> {
+    let entries = read(path)?;
+    for (i, entry) in entries.iter().enumerate() {
+        let role = if i + 1 == entries.len() {
+            "primary"
+        } else {
+            "decode only"
+        };
+        println!(
+            "{}  added {}  {}",
+            check_value(&entry.key),
+            format_time(entry.added_at as i64),
+            role
+        );
+    }
+    Ok(())
+}
+
+// Drop keys replaced more than `ttl` seconds ago: every token they minted
+// has expired by now.
+fn retire(path: &str, ttl: u32) -> Result<(), String> {
+    if ttl == 0 {
+        return Err("retire requires the token --ttl".to_string());
+    }
+    let mut entries = read(path)?;
+    let now = now();
+    let keep_from = entries
+        .windows(2)
+        .take_while(|pair| pair[1].added_at + u64::from(ttl) < now)
+        .count();
+    for entry in entries.drain(..keep_from) {
+        println!("retired key {}", check_value(&entry.key));
+    }
+    write(path, &entries)
+}
+
+fn read(path: &str) -> Result<Vec<Entry>, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let invalid = || format!("{}:{}: expected `<hex key> <added at>`", path, i + 1);
+            let mut fields = line.split_whitespace();
+            let key = decode_hex(fields.next().ok_or_else(invalid)?).map_err(|_| invalid())?;
+            let added_at = fields
+                .next()
+                .and_then(|field| field.parse().ok())
+                .ok_or_else(invalid)?;
+            if key.len() != 32 || fields.next().is_some() {
+                return Err(invalid());
+            }
+            Ok(Entry { key, added_at })
+        })
+        .collect()
+}
+
+// Replace the keyring atomically, so a failed write never loses keys.
+fn write(path: &str, entries: &[Entry]) -> Result<(), String> {
+    let contents: String = entries
+        .iter()
+        .map(|entry| format!("{} {}\n", encode_hex(&entry.key), entry.added_at))
+        .collect();
+    // Per-process temp name, so a file left behind by a crashed run never
+    // blocks later rotations; one left by a previous process with the same
+    // pid is stale and removed.
+    let tmp = format!("{}.{}.tmp", path, std::process::id());
+    let _ = fs::remove_file(&tmp);
+    let result = write_secret_file(&tmp, contents.as_bytes())
+        .and_then(|()| fs::rename(&tmp, path).map_err(|err| format!("{}: {}", path, err)));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+// Identifies a key without revealing it: the start of the tag of an empty
+// message sealed under the key with an all-zero nonce.
+fn check_value(key: &[u8]) -> String {
+    let cipher = XChaCha20Poly1305::new_from_slice(key).expect("32 bytes key");
+    let tag = cipher
+        .encrypt(XNonce::from_slice(&[0u8; 24]), &[][..])
+        .expect("sealing an empty message");
+    encode_hex(&tag[..4])
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
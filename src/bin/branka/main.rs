@@ -0,0 +1,296 @@
+// Command line tool for minting and debugging branka tokens.
+
+mod config;
+mod encoding;
+mod inspect;
+mod keys;
+mod sealed;
+mod size_report;
+mod stream;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+use branka::{Branka, Keyring};
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+
+use encoding::{decode_base64, decode_hex, decode_json, encode_base64, encode_hex, encode_json};
+
+const USAGE: &str = "\
+usage: branka <command> [options]
+
+commands:
+  encode            read a payload from stdin and print its token
+  decode [TOKEN]    decode TOKEN (or stdin) and print the payload
+  keygen            generate a random 32 bytes key
+  inspect TOKEN     show the token header; with a key, also its payload
+  size-report       print token sizes for the payload given with --payload
+  keys rotate       add a new primary key to the --keyring file
+  keys list         list the keys of the --keyring file
+  keys retire       drop keys replaced more than --ttl seconds ago
+  seal              read NAME=VALUE lines from stdin and print them as a
+                    sealed config file
+  unseal [FILE]     print the NAME=VALUE settings of a sealed config file
+                    (or stdin)
+
+options:
+  --key-file PATH   read the 32 bytes key from PATH (raw, hex or base64)
+  --key-env VAR     read the hex or base64 key from $VAR
+                    (default: $BRANKA_KEY)
+  --keyring PATH    keyring file: encode with its primary key, decode with
+                    any of its keys
+  --ttl SECONDS     token time to live, checked on decode (default: 0, none)
+  --format FORMAT   payload format: raw, hex, base64 or json (default: raw,
+                    hex for inspect);
+                    key format for keygen: hex or base64 (default: hex)
+  --out PATH        keygen: write the key to PATH (mode 0600) instead of stdout
+  --payload PATH    size-report: payload file to measure
+  --stream          encode/decode: one payload or token per stdin line, one
+                    token or payload per stdout line (use hex or base64 for
+                    payloads that may contain newlines)
+
+Defaults for key_file, key_env, keyring, ttl and format are read from
+$BRANKA_CONFIG or ~/.config/branka/config.toml, as `name = value` lines.
+";
+
+// Payload and key encodings accepted on stdin and printed on stdout.
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Raw,
+    Hex,
+    Base64,
+    Json,
+}
+
+#[derive(Clone, Default)]
+struct Options {
+    key_file: Option<String>,
+    key_env: Option<String>,
+    keyring: Option<String>,
+    ttl: u32,
+    format: Option<Format>,
+    out: Option<String>,
+    payload: Option<String>,
+    stream: bool,
+    args: Vec<String>,
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+
+    let result = config::load()
+        .and_then(|defaults| parse_options(args, defaults))
+        .and_then(|options| match command.as_str() {
+            "encode" if options.stream => stream::encode(&options),
+            "decode" if options.stream => stream::decode(&options),
+            "encode" => encode(&options),
+            "decode" => decode(&options),
+            "keygen" => keygen(&options),
+            "inspect" => inspect::inspect(&options),
+            "keys" => keys::keys(&options),
+            "size-report" => size_report::size_report(&options),
+            "seal" => sealed::seal(&options),
+            "unseal" => sealed::unseal(&options),
+            "-h" | "--help" | "help" => {
+                print!("{}", USAGE);
+                Ok(())
+            }
+            _ => Err(format!("unknown command {:?}\n\n{}", command, USAGE)),
+        });
+
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+// Flags override the config file `defaults`.
+fn parse_options<I: Iterator<Item = String>>(
+    mut args: I,
+    mut options: Options,
+) -> Result<Options, String> {
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} requires a value", name));
+        match arg.as_str() {
+            // A key source given on the command line replaces the configured one.
+            "--key-file" | "--key-env" | "--keyring" => {
+                let value = Some(value(&arg)?);
+                options.key_file = None;
+                options.key_env = None;
+                options.keyring = None;
+                match arg.as_str() {
+                    "--key-file" => options.key_file = value,
+                    "--key-env" => options.key_env = value,
+                    _ => options.keyring = value,
+                }
+            }
+            "--ttl" => {
+                options.ttl = value("--ttl")?
+                    .parse()
+                    .map_err(|_| "--ttl must be a number of seconds".to_string())?
+            }
+            "--format" => options.format = Some(parse_format(&value("--format")?)?),
+            "--out" => options.out = Some(value("--out")?),
+            "--payload" => options.payload = Some(value("--payload")?),
+            "--stream" => options.stream = true,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ => options.args.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn parse_format(format: &str) -> Result<Format, String> {
+    match format {
+        "raw" => Ok(Format::Raw),
+        "hex" => Ok(Format::Hex),
+        "base64" => Ok(Format::Base64),
+        "json" => Ok(Format::Json),
+        other => Err(format!("unknown format {:?}", other)),
+    }
+}
+
+fn encode(options: &Options) -> Result<(), String> {
+    let keyring = load_keyring(options)?;
+    let payload = read_payload(options.format.unwrap_or(Format::Raw))?;
+    let token = keyring
+        .try_encode(&payload)
+        .map_err(|err| format!("encode failed: {:?}", err))?;
+    println!("{}", token);
+    Ok(())
+}
+
+fn decode(options: &Options) -> Result<(), String> {
+    let keyring = load_keyring(options)?;
+    let token = match options.args.first() {
+        Some(token) => token.clone(),
+        None => read_stdin_string()?.trim().to_string(),
+    };
+    let payload = keyring
+        .decode(&token)
+        .map_err(|err| format!("decode failed: {:?}", err))?;
+    write_payload(options.format.unwrap_or(Format::Raw), &payload)
+}
+
+fn keygen(options: &Options) -> Result<(), String> {
+    let mut key = [0u8; 32];
+    OsRng
+        .try_fill_bytes(&mut key)
+        .map_err(|err| format!("generating key: {}", err))?;
+
+    let text = match options.format.unwrap_or(Format::Hex) {
+        Format::Hex => encode_hex(&key),
+        Format::Base64 => encode_base64(&key),
+        Format::Raw | Format::Json => return Err("keygen prints hex or base64 keys".to_string()),
+    };
+
+    match &options.out {
+        Some(path) => write_secret_file(path, format!("{}\n", text).as_bytes()),
+        None => {
+            println!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+// Create `path` readable by its owner only; refuses to overwrite a key.
+fn write_secret_file(path: &str, contents: &[u8]) -> Result<(), String> {
+    let mut open = fs::OpenOptions::new();
+    open.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut open, 0o600);
+    open.open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|err| format!("{}: {}", path, err))
+}
+
+fn load_keyring(options: &Options) -> Result<Keyring, String> {
+    match &options.keyring {
+        Some(path) => keys::load_keyring(path, options.ttl),
+        None => load_branka(options).map(Keyring::new),
+    }
+}
+
+fn load_branka(options: &Options) -> Result<Branka, String> {
+    Ok(Branka::new(&load_key(options)?, options.ttl))
+}
+
+fn load_key(options: &Options) -> Result<Vec<u8>, String> {
+    let key = match &options.key_file {
+        Some(path) => {
+            let contents = fs::read(path).map_err(|err| format!("{}: {}", path, err))?;
+            if contents.len() == 32 {
+                contents
+            } else {
+                let text = String::from_utf8(contents)
+                    .map_err(|_| format!("{}: key must be 32 raw bytes, hex or base64", path))?;
+                decode_key_text(text.trim())?
+            }
+        }
+        None => {
+            let var = options.key_env.as_deref().unwrap_or("BRANKA_KEY");
+            let text = env::var(var).map_err(|_| match &options.key_env {
+                Some(_) => format!("no key: {} is not set", var),
+                None => "no key: set BRANKA_KEY or pass --key-file".to_string(),
+            })?;
+            decode_key_text(text.trim())?
+        }
+    };
+    if key.len() != 32 {
+        return Err(format!("key must be 32 bytes, got {}", key.len()));
+    }
+    Ok(key)
+}
+
+fn read_payload(format: Format) -> Result<Vec<u8>, String> {
+    let mut input = Vec::new();
+    io::stdin()
+        .read_to_end(&mut input)
+        .map_err(|err| format!("reading stdin: {}", err))?;
+    match format {
+        Format::Raw => Ok(input),
+        Format::Hex | Format::Base64 | Format::Json => {
+            let text = String::from_utf8(input).map_err(|_| "invalid payload".to_string())?;
+            decode_text(format, text.trim())
+        }
+    }
+}
+
+fn write_payload(format: Format, payload: &[u8]) -> Result<(), String> {
+    let mut stdout = io::stdout();
+    match format {
+        Format::Raw => stdout.write_all(payload),
+        Format::Hex => writeln!(stdout, "{}", encode_hex(payload)),
+        Format::Base64 => writeln!(stdout, "{}", encode_base64(payload)),
+        Format::Json => writeln!(stdout, "{}", encode_json(payload)?),
+    }
+    .map_err(|err| format!("writing stdout: {}", err))
+}
+
+fn read_stdin_string() -> Result<String, String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|err| format!("reading stdin: {}", err))?;
+    Ok(input)
+}
+
+fn decode_text(format: Format, text: &str) -> Result<Vec<u8>, String> {
+    match format {
+        Format::Hex => decode_hex(text),
+        Format::Json => decode_json(text),
+        _ => decode_base64(text),
+    }
+}
+
+// Keys are accepted as 64 hex digits or base64, as printed by keygen.
+fn decode_key_text(text: &str) -> Result<Vec<u8>, String> {
+    if text.len() == 64 {
+        decode_hex(text)
+    } else {
+        decode_base64(text)
+    }
+}
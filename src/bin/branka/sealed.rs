@@ -0,0 +1,66 @@
+// `branka seal` / `branka unseal`: sealed config files, for keeping .env
+// style secrets encrypted at rest.
+
+use std::fs;
+
+use branka::{open_config, seal_config};
+
+use super::{load_key, read_stdin_string, Options};
+
+pub fn seal(options: &Options) -> Result<(), String> {
+    let key = load_key(options)?;
+    let input = read_stdin_string()?;
+    let settings = parse_env(&input)?;
+    let settings: Vec<(&str, &[u8])> = settings
+        .iter()
+        .map(|(name, value)| (*name, value.as_bytes()))
+        .collect();
+    let file = seal_config(&key, &settings).map_err(|err| format!("sealing failed: {:?}", err))?;
+    print!("{}", file);
+    Ok(())
+}
+
+pub fn unseal(options: &Options) -> Result<(), String> {
+    let key = load_key(options)?;
+    let file = match options.args.first() {
+        Some(path) => fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?,
+        None => read_stdin_string()?,
+    };
+    let settings = open_config(&key, &file).map_err(|err| format!("unseal failed: {:?}", err))?;
+    for (name, value) in settings {
+        let value = String::from_utf8(value).map_err(|_| format!("{}: value is not text", name))?;
+        println!("{}={}", name, value);
+    }
+    Ok(())
+}
+
+// `NAME=VALUE` lines; blank lines and `#` comments are skipped.
+fn parse_env(input: &str) -> Result<Vec<(&str, &str)>, String> {
+    let mut settings = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                settings.push((name.trim(), value.trim()))
+            }
+            _ => return Err(format!("line {}: expected NAME=VALUE", i + 1)),
+        }
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        let settings = parse_env("# secrets\nA=1\n\n B = x=y \n").unwrap();
+        assert_eq!(settings, vec![("A", "1"), ("B", "x=y")]);
+        assert!(parse_env("A").is_err());
+        assert!(parse_env("=1").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+// `branka size-report`: how large a payload's tokens are in each transport.
+
+use std::fs;
+
+use branka::{chunk_token, to_bearer, Branka, DEFAULT_CHUNK_SIZE, TOKEN_OVERHEAD};
+
+use super::Options;
+
+pub fn size_report(options: &Options) -> Result<(), String> {
+    let path = options
+        .payload
+        .as_ref()
+        .ok_or_else(|| "size-report requires --payload FILE".to_string())?;
+    let payload = fs::read(path).map_err(|err| format!("{}: {}", path, err))?;
+
+    // Token sizes only depend on the payload length, any key will do.
+    let token = Branka::new(&[0u8; 32], 0).encode(&payload);
+    let cookies = chunk_token("t", &token, DEFAULT_CHUNK_SIZE);
+    // As sent in a Cookie header: `name=value` for each cookie.
+    let cookie_bytes: usize = cookies
+        .iter()
+        .map(|(name, value)| name.len() + 1 + value.len())
+        .sum();
+
+    println!("{:<28} {:>10}", "representation", "bytes");
+    println!("{:<28} {:>10}", "payload", payload.len());
+    println!(
+        "{:<28} {:>10}",
+        "raw binary token",
+        payload.len() + TOKEN_OVERHEAD
+    );
+    println!("{:<28} {:>10}", "base62 token", token.len());
+    println!(
+        "{:<28} {:>10}",
+        "Authorization: Bearer value",
+        to_bearer(&token).len()
+    );
+    println!(
+        "{:<28} {:>10}",
+        format!(
+            "{} cookie(s) of <= {} bytes",
+            cookies.len(),
+            DEFAULT_CHUNK_SIZE
+        ),
+        cookie_bytes
+    );
+    Ok(())
+}
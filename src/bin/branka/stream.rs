@@ -0,0 +1,121 @@
+// `--stream`: one payload or token per line, for shell pipelines and batch jobs.
+
+use std::io::{self, BufRead, BufWriter, Write};
+
+use branka::Keyring;
+
+use super::{decode_text, encode_base64, encode_hex, encode_json, load_keyring, Format, Options};
+
+pub fn encode(options: &Options) -> Result<(), String> {
+    let keyring = load_keyring(options)?;
+    let format = options.format.unwrap_or(Format::Raw);
+    run(|line| encode_line(&keyring, format, line))
+}
+
+pub fn decode(options: &Options) -> Result<(), String> {
+    let keyring = load_keyring(options)?;
+    let format = options.format.unwrap_or(Format::Raw);
+    run(|line| decode_line(&keyring, format, line))
+}
+
+// Feed every stdin line through `process`. Output stays line-aligned with the
+// input: a line that fails prints an empty line, and the error goes to stderr.
+fn run<F: Fn(&[u8]) -> Result<Vec<u8>, String>>(process: F) -> Result<(), String> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let failed = process_lines(
+        stdin.lock(),
+        BufWriter::new(stdout.lock()),
+        |number, line| process(line).map_err(|err| eprintln!("error: line {}: {}", number, err)),
+    )
+    .map_err(|err| format!("streaming: {}", err))?;
+
+    if failed > 0 {
+        return Err(format!("{} line(s) failed", failed));
+    }
+    Ok(())
+}
+
+fn process_lines<R, W, F>(mut input: R, mut output: W, mut process: F) -> io::Result<usize>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(usize, &[u8]) -> Result<Vec<u8>, ()>,
+{
+    let mut line = Vec::new();
+    let mut number = 0;
+    let mut failed = 0;
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        number += 1;
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+
+        match process(number, &line) {
+            Ok(out) => output.write_all(&out)?,
+            Err(()) => failed += 1,
+        }
+        output.write_all(b"\n")?;
+    }
+    output.flush()?;
+    Ok(failed)
+}
+
+fn encode_line(keyring: &Keyring, format: Format, line: &[u8]) -> Result<Vec<u8>, String> {
+    let payload = match format {
+        Format::Raw => line.to_vec(),
+        _ => decode_text(format, text(line)?)?,
+    };
+    keyring
+        .try_encode(&payload)
+        .map(String::into_bytes)
+        .map_err(|err| format!("encode failed: {:?}", err))
+}
+
+fn decode_line(keyring: &Keyring, format: Format, line: &[u8]) -> Result<Vec<u8>, String> {
+    let payload = keyring
+        .decode(text(line)?.trim())
+        .map_err(|err| format!("decode failed: {:?}", err))?;
+    Ok(match format {
+        Format::Raw => payload,
+        Format::Hex => encode_hex(&payload).into_bytes(),
+        Format::Base64 => encode_base64(&payload).into_bytes(),
+        Format::Json => encode_json(&payload)?.into_bytes(),
+    })
+}
+
+fn text(line: &[u8]) -> Result<&str, String> {
+    std::str::from_utf8(line).map_err(|_| "line is not valid UTF-8".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_round_trip() {
+        let keyring = Keyring::new(branka::Branka::new(&[9u8; 32], 0));
+        let mut tokens = Vec::new();
+        let failed = process_lines(&b"one\r\ntwo\n\nthree"[..], &mut tokens, |_, line| {
+            encode_line(&keyring, Format::Raw, line).map_err(|_| ())
+        })
+        .unwrap();
+        assert_eq!(failed, 0);
+
+        let input = [&tokens[..], b"garbage\n"].concat();
+        let mut payloads = Vec::new();
+        let failed = process_lines(&input[..], &mut payloads, |_, line| {
+            decode_line(&keyring, Format::Raw, line).map_err(|_| ())
+        })
+        .unwrap();
+        assert_eq!(failed, 1);
+        assert_eq!(payloads, b"one\ntwo\n\nthree\n\n");
+    }
+}
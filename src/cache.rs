@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{Branka, BrankaError};
+
+// Memoizes successful decodes, for tokens presented on every request (a
+// session token). Entries are keyed by the full token string, so only the
+// exact token that was verified can hit; they are dropped once the token
+// expires. When full, expired entries are evicted first, then the least
+// recently used one.
+//
+// Cache hits skip decryption, and so the audit sink of `branka`.
+pub struct CachedDecoder {
+    branka: Branka,
+    capacity: usize,
+    cache: Mutex<Cache>,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<String, Entry>,
+    // Incremented on every access, to order entries by last use.
+    clock: u64,
+}
+
+struct Entry {
+    payload: Vec<u8>,
+    expires_at: u32,
+    last_used: u64,
+}
+
+impl CachedDecoder {
+    // capacity: maximum number of cached tokens. Eviction scans the cache,
+    // so keep it in the thousands rather than millions.
+    pub fn new(branka: Branka, capacity: usize) -> CachedDecoder {
+        CachedDecoder {
+            branka,
+            capacity,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    pub fn decode(&self, token: &str) -> Result<Vec<u8>, BrankaError> {
+        let now = self.branka.now();
+        {
+            let mut cache = self.cache.lock().unwrap();
+            cache.clock += 1;
+            let clock = cache.clock;
+            match cache.entries.get_mut(token) {
+                Some(entry) if entry.expires_at >= now => {
+                    entry.last_used = clock;
+                    return Ok(entry.payload.clone());
+                }
+                Some(_) => {
+                    cache.entries.remove(token);
+                }
+                None => {}
+            }
+        }
+
+        let decoded = self.branka.decode_full(token)?;
+        if self.capacity > 0 {
+            let mut cache = self.cache.lock().unwrap();
+            if cache.entries.len() >= self.capacity {
+                cache.evict(now);
+            }
+            let last_used = cache.clock;
+            cache.entries.insert(
+                token.to_string(),
+                Entry {
+                    payload: decoded.payload.clone(),
+                    expires_at: self.branka.expires_at(decoded.timestamp),
+                    last_used,
+                },
+            );
+        }
+        Ok(decoded.payload)
+    }
+
+    // Number of cached tokens.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().entries.clear();
+    }
+}
+
+impl Cache {
+    fn evict(&mut self, now: u32) {
+        self.entries.retain(|_, entry| entry.expires_at >= now);
+        if self.entries.is_empty() {
+            return;
+        }
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(token, _)| token.clone())
+            .unwrap();
+        self.entries.remove(&oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_decoder_evicts_lru() {
+        let branka = Branka::new(&[5u8; 32], 0);
+        let (a, b, c) = (
+            branka.encode(b"a"),
+            branka.encode(b"b"),
+            branka.encode(b"c"),
+        );
+        let decoder = CachedDecoder::new(branka, 2);
+
+        assert_eq!(decoder.decode(&a).unwrap(), b"a");
+        assert_eq!(decoder.decode(&b).unwrap(), b"b");
+        assert_eq!(decoder.decode(&a).unwrap(), b"a");
+        assert_eq!(decoder.decode(&c).unwrap(), b"c");
+        assert_eq!(decoder.len(), 2);
+        assert!(decoder.cache.lock().unwrap().entries.contains_key(&a));
+        assert!(!decoder.cache.lock().unwrap().entries.contains_key(&b));
+
+        assert_eq!(
+            decoder.decode("not-base62"),
+            Err(BrankaError::InvalidBase62)
+        );
+        assert_eq!(decoder.len(), 2);
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::BrankaError;
+
+// Browsers cap a cookie (name + value + attributes) at 4096 bytes.
+pub const DEFAULT_CHUNK_SIZE: usize = 3800;
+// Upper bound on the number of chunks accepted when reassembling.
+const MAX_CHUNKS: usize = 32;
+
+// Split a token into `name.0`, `name.1`, ... cookies whose values are at most
+// `chunk_size` bytes, plus an `<index>/<count>.` prefix.
+// Every chunk carries the chunk count, so reassembly can tell a missing or
+// extra chunk from a tampered one; the reassembled token is then still
+// authenticated as a whole on decode.
+pub fn chunk_token(name: &str, token: &str, chunk_size: usize) -> Vec<(String, String)> {
+    assert!(chunk_size > 0, "chunk_size must be positive");
+
+    // Tokens are base62, so splitting on byte boundaries is safe.
+    let mut parts: Vec<&str> = token
+        .as_bytes()
+        .chunks(chunk_size)
+        .map(|c| core::str::from_utf8(c).unwrap())
+        .collect();
+    if parts.is_empty() {
+        parts.push("");
+    }
+    let count = parts.len();
+
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            (
+                format!("{}.{}", name, i),
+                format!("{}/{}.{}", i, count, part),
+            )
+        })
+        .collect()
+}
+
+// Reassemble a token split by `chunk_token`.
+// `get` looks up a cookie value by name in the incoming request.
+// Fails with MissingChunk if a chunk is absent, and InvalidChunks if the
+// chunks disagree on their count or position, or one is left over.
+pub fn reassemble_token<'a, F>(name: &str, get: F) -> Result<String, BrankaError>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    let mut token = String::new();
+    let mut count = 1;
+    let mut i = 0;
+    while i < count {
+        let chunk = get(&format!("{}.{}", name, i)).ok_or(BrankaError::MissingChunk)?;
+        let (index, chunk_count, data) = parse_chunk(chunk).ok_or(BrankaError::InvalidChunks)?;
+        if i == 0 {
+            count = chunk_count;
+        }
+        if index != i || chunk_count != count || count == 0 || count > MAX_CHUNKS {
+            return Err(BrankaError::InvalidChunks);
+        }
+        token.push_str(data);
+        i += 1;
+    }
+    if get(&format!("{}.{}", name, count)).is_some() {
+        return Err(BrankaError::InvalidChunks);
+    }
+    Ok(token)
+}
+
+// `<index>/<count>.<data>`
+fn parse_chunk(chunk: &str) -> Option<(usize, usize, &str)> {
+    let (position, data) = chunk.split_once('.')?;
+    let (index, count) = position.split_once('/')?;
+    Some((index.parse().ok()?, count.parse().ok()?, data))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Branka;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_chunk_reassemble() {
+        let key = [7u8; 32];
+        let branka = Branka::new(&key, 3000).with_max_token_len(16 * 1024);
+        let token = branka.encode(&[42u8; 6000]);
+
+        let cookies = chunk_token("session", &token, DEFAULT_CHUNK_SIZE);
+        assert!(cookies.len() > 1);
+        assert!(cookies
+            .iter()
+            .all(|(_, v)| v.len() <= DEFAULT_CHUNK_SIZE + 6));
+
+        let jar: HashMap<String, String> = cookies.into_iter().collect();
+        let reassembled = reassemble_token("session", |n| jar.get(n).map(|v| v.as_str())).unwrap();
+        assert_eq!(reassembled, token);
+        assert_eq!(branka.decode(&reassembled).unwrap(), vec![42u8; 6000]);
+    }
+
+    #[test]
+    fn test_reassemble_missing_chunk() {
+        let cookies = chunk_token("s", "abcdefghij", 4);
+        let mut jar: HashMap<String, String> = cookies.into_iter().collect();
+        jar.remove("s.2");
+        assert_eq!(
+            reassemble_token("s", |n| jar.get(n).map(|v| v.as_str())),
+            Err(BrankaError::MissingChunk)
+        );
+    }
+
+    #[test]
+    fn test_reassemble_inconsistent_chunks() {
+        let reassemble = |jar: &HashMap<String, String>| {
+            reassemble_token("s", |n| jar.get(n).map(|v| v.as_str()))
+        };
+        let jar: HashMap<String, String> = chunk_token("s", "abcdefghij", 4).into_iter().collect();
+        assert_eq!(reassemble(&jar).unwrap(), "abcdefghij");
+
+        // A stale chunk from a longer token.
+        let mut extra = jar.clone();
+        extra.insert("s.3".into(), "3/4.klmn".into());
+        assert_eq!(reassemble(&extra), Err(BrankaError::InvalidChunks));
+
+        // The count is bound into every chunk, not only the first.
+        let mut shortened = jar.clone();
+        shortened.insert("s.0".into(), "0/2.abcd".into());
+        assert_eq!(reassemble(&shortened), Err(BrankaError::InvalidChunks));
+
+        let mut swapped = jar;
+        let first = swapped["s.0"].clone();
+        swapped.insert("s.0".into(), swapped["s.1"].clone());
+        swapped.insert("s.1".into(), first);
+        assert_eq!(reassemble(&swapped), Err(BrankaError::InvalidChunks));
+    }
+}
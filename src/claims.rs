@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use crate::{Branka, BrankaError, DecodeFailure, Payload};
+
+// Registered claim names, as in JWT.
+const SUBJECT: &str = "sub";
+const AUDIENCE: &str = "aud";
+const EXPIRES_AT: &str = "exp";
+
+// Claims of a verified token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Claims {
+    claims: HashMap<String, String>,
+}
+
+impl Claims {
+    pub fn subject(&self) -> Option<&str> {
+        self.get(SUBJECT)
+    }
+
+    pub fn audience(&self) -> Option<&str> {
+        self.get(AUDIENCE)
+    }
+
+    // Unix time set by TokenBuilder::ttl, if any.
+    pub fn expires_at(&self) -> Option<u32> {
+        self.get(EXPIRES_AT).and_then(|exp| exp.parse().ok())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.claims.get(name).map(String::as_str)
+    }
+
+    pub fn into_map(self) -> HashMap<String, String> {
+        self.claims
+    }
+}
+
+// A failed check reported by TokenVerifier::validate_detailed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationFailure {
+    // The token could not be decoded at all; no other check was possible.
+    Undecodable(DecodeFailure),
+    // The payload is not a claims map, or its `exp` claim is not a number.
+    InvalidClaims,
+    // Expired by the instance TTL or by the token's own `exp` claim.
+    Expired {
+        expires_at: u32,
+    },
+    // An expected claim is missing (`actual` is None) or has another value.
+    ClaimMismatch {
+        name: String,
+        expected: String,
+        actual: Option<String>,
+    },
+}
+
+// Fluent token construction over string claims (see Branka::encode_map):
+//
+//     let token = branka.token().subject("u123").audience("billing")
+//         .ttl(300).claim("role", "admin").build()?;
+pub struct TokenBuilder<'a> {
+    branka: &'a Branka,
+    claims: HashMap<String, String>,
+}
+
+impl TokenBuilder<'_> {
+    pub fn subject(self, subject: &str) -> Self {
+        self.claim(SUBJECT, subject)
+    }
+
+    pub fn audience(self, audience: &str) -> Self {
+        self.claim(AUDIENCE, audience)
+    }
+
+    // Expire this token `ttl` seconds from now, whatever the TTL of the
+    // Branka instance.
+    pub fn ttl(self, ttl: u32) -> Self {
+        let expires_at = self.branka.now().saturating_add(ttl);
+        self.claim(EXPIRES_AT, &expires_at.to_string())
+    }
+
+    pub fn claim(mut self, name: &str, value: &str) -> Self {
+        self.claims.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<String, BrankaError> {
+        self.branka.encode_map(&self.claims)
+    }
+}
+
+// Fluent verification of tokens from TokenBuilder: the expected claims must
+// be present with exactly these values.
+pub struct TokenVerifier<'a> {
+    branka: &'a Branka,
+    expected: Vec<(String, String)>,
+}
+
+impl TokenVerifier<'_> {
+    pub fn subject(self, subject: &str) -> Self {
+        self.claim(SUBJECT, subject)
+    }
+
+    pub fn audience(self, audience: &str) -> Self {
+        self.claim(AUDIENCE, audience)
+    }
+
+    pub fn claim(mut self, name: &str, value: &str) -> Self {
+        self.expected.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // Run every check instead of stopping at the first failure, for support
+    // tooling diagnosing rejected tokens. Only a token that cannot be
+    // decoded at all stops early. Returns the claims when every check passes.
+    pub fn validate_detailed(&self, token: &str) -> Result<Claims, Vec<ValidationFailure>> {
+        let untimed = self.branka.without_ttl();
+        let decoded = untimed
+            .decode_full(token)
+            .map_err(|err| vec![ValidationFailure::Undecodable(untimed.diagnose(token, err))])?;
+
+        let mut failures = Vec::new();
+        let now = self.branka.now();
+        let expires_at = self.branka.expires_at(decoded.timestamp);
+        if expires_at < now {
+            failures.push(ValidationFailure::Expired { expires_at });
+        }
+
+        let claims = match HashMap::from_payload(&decoded.payload) {
+            Ok(claims) => Claims { claims },
+            Err(_) => {
+                failures.push(ValidationFailure::InvalidClaims);
+                return Err(failures);
+            }
+        };
+        match (claims.get(EXPIRES_AT), claims.expires_at()) {
+            (None, _) => {}
+            (Some(_), None) => failures.push(ValidationFailure::InvalidClaims),
+            (Some(_), Some(expires_at)) if expires_at < now => {
+                failures.push(ValidationFailure::Expired { expires_at })
+            }
+            _ => {}
+        }
+        for (name, expected) in &self.expected {
+            let actual = claims.get(name);
+            if actual != Some(expected.as_str()) {
+                failures.push(ValidationFailure::ClaimMismatch {
+                    name: name.clone(),
+                    expected: expected.clone(),
+                    actual: actual.map(String::from),
+                });
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(claims)
+        } else {
+            Err(failures)
+        }
+    }
+
+    // Expired tokens (by the instance TTL or their own) fail with Expired,
+    // tokens missing an expected claim with InvalidClaim.
+    pub fn verify(&self, token: &str) -> Result<Claims, BrankaError> {
+        let claims = Claims {
+            claims: self.branka.decode_map(token)?,
+        };
+        if claims.get(EXPIRES_AT).is_some() {
+            let expires_at = claims.expires_at().ok_or(BrankaError::InvalidPayload)?;
+            if expires_at < self.branka.now() {
+                return Err(BrankaError::Expired);
+            }
+        }
+        if self
+            .expected
+            .iter()
+            .any(|(name, value)| claims.get(name) != Some(value.as_str()))
+        {
+            return Err(BrankaError::InvalidClaim);
+        }
+        Ok(claims)
+    }
+}
+
+impl Branka {
+    pub fn token(&self) -> TokenBuilder<'_> {
+        TokenBuilder {
+            branka: self,
+            claims: HashMap::new(),
+        }
+    }
+
+    pub fn verifier(&self) -> TokenVerifier<'_> {
+        TokenVerifier {
+            branka: self,
+            expected: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fluent_claims() {
+        let branka = Branka::new(&[8u8; 32], 0);
+        let token = branka
+            .token()
+            .subject("u123")
+            .audience("billing")
+            .ttl(300)
+            .claim("role", "admin")
+            .build()
+            .unwrap();
+
+        let claims = branka
+            .verifier()
+            .audience("billing")
+            .verify(&token)
+            .unwrap();
+        assert_eq!(claims.subject(), Some("u123"));
+        assert_eq!(claims.get("role"), Some("admin"));
+        assert!(claims.expires_at().unwrap() >= branka.now() + 299);
+
+        assert_eq!(
+            branka.verifier().audience("search").verify(&token),
+            Err(BrankaError::InvalidClaim)
+        );
+        let expired = branka.token().claim("exp", "1").build().unwrap();
+        assert_eq!(
+            branka.verifier().verify(&expired),
+            Err(BrankaError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_validate_detailed_reports_every_failure() {
+        let branka = Branka::new(&[8u8; 32], 0);
+        let token = branka
+            .token()
+            .audience("billing")
+            .claim("exp", "1")
+            .build()
+            .unwrap();
+
+        let failures = branka
+            .verifier()
+            .audience("search")
+            .subject("u1")
+            .validate_detailed(&token)
+            .unwrap_err();
+        assert_eq!(
+            failures,
+            vec![
+                ValidationFailure::Expired { expires_at: 1 },
+                ValidationFailure::ClaimMismatch {
+                    name: "aud".into(),
+                    expected: "search".into(),
+                    actual: Some("billing".into()),
+                },
+                ValidationFailure::ClaimMismatch {
+                    name: "sub".into(),
+                    expected: "u1".into(),
+                    actual: None,
+                },
+            ]
+        );
+
+        let other = Branka::new(&[9u8; 32], 0);
+        assert!(matches!(
+            other.verifier().validate_detailed(&token).unwrap_err()[..],
+            [ValidationFailure::Undecodable(
+                DecodeFailure::WrongKeyOrTampered { .. }
+            )]
+        ));
+    }
+}
@@ -0,0 +1,16 @@
+// Time source used to stamp and expire tokens, in unix seconds.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> u32;
+}
+
+// Wall clock, the default.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> u32 {
+        crate::get_timestamp()
+    }
+}
@@ -0,0 +1,151 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::net::IpAddr;
+
+use crate::{Branka, BrankaError};
+
+// Domain separation for binding values.
+const CONTEXT_LABEL: &[u8] = b"branka:context:v1";
+
+const IP: u8 = 1;
+const USER_AGENT: u8 = 2;
+const TLS_SESSION: u8 = 3;
+
+// Attributes of the request presenting or receiving a token.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestContext<'a> {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<&'a str>,
+    pub tls_session_id: Option<&'a [u8]>,
+}
+
+// Binds tokens to the client they were issued to: the selected request
+// attributes are authenticated with the token (see encode_aad), so a stolen
+// token fails to decode, with InvalidData, from a client that doesn't match.
+// By default the client's IP prefix (/24 or /64) and user agent are bound.
+pub struct ContextBinder {
+    branka: Branka,
+    ip_prefix: Option<(u8, u8)>,
+    user_agent: bool,
+    tls_session: bool,
+}
+
+impl ContextBinder {
+    pub fn new(branka: Branka) -> ContextBinder {
+        ContextBinder {
+            branka,
+            ip_prefix: Some((24, 64)),
+            user_agent: true,
+            tls_session: false,
+        }
+    }
+
+    // Bind the client address, truncated to the given IPv4 and IPv6 prefix
+    // lengths so clients can move within their network; None to not bind it.
+    pub fn ip_prefix(mut self, prefix: Option<(u8, u8)>) -> Self {
+        self.ip_prefix = prefix.map(|(v4, v6)| (v4.min(32), v6.min(128)));
+        self
+    }
+
+    pub fn user_agent(mut self, bind: bool) -> Self {
+        self.user_agent = bind;
+        self
+    }
+
+    // Bind the TLS session id; tokens then die with the TLS session.
+    pub fn tls_session(mut self, bind: bool) -> Self {
+        self.tls_session = bind;
+        self
+    }
+
+    pub fn encode(&self, data: &[u8], context: &RequestContext) -> Result<String, BrankaError> {
+        self.branka.encode_aad(data, &self.binding(context))
+    }
+
+    pub fn decode(&self, token: &str, context: &RequestContext) -> Result<Vec<u8>, BrankaError> {
+        self.branka.decode_aad(token, &self.binding(context))
+    }
+
+    // The stable binding value for `context`: each selected attribute as
+    // tag || length (u32 BE) || value, a missing one as an empty value.
+    pub fn binding(&self, context: &RequestContext) -> Vec<u8> {
+        let mut binding = CONTEXT_LABEL.to_vec();
+        if let Some((v4, v6)) = self.ip_prefix {
+            let ip = context.ip.map(|ip| match ip {
+                IpAddr::V4(ip) => masked(&ip.octets(), v4),
+                IpAddr::V6(ip) => masked(&ip.octets(), v6),
+            });
+            push(&mut binding, IP, ip.as_deref().unwrap_or_default());
+        }
+        if self.user_agent {
+            let user_agent = context.user_agent.unwrap_or_default();
+            push(&mut binding, USER_AGENT, user_agent.as_bytes());
+        }
+        if self.tls_session {
+            push(
+                &mut binding,
+                TLS_SESSION,
+                context.tls_session_id.unwrap_or_default(),
+            );
+        }
+        binding
+    }
+}
+
+// The first `bits` of `address`, followed by the prefix length.
+fn masked(address: &[u8], bits: u8) -> Vec<u8> {
+    let mut masked: Vec<u8> = address
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| {
+            let keep = usize::from(bits).saturating_sub(i * 8).min(8);
+            byte & !(0xFFu8.checked_shr(keep as u32).unwrap_or(0))
+        })
+        .collect();
+    masked.push(bits);
+    masked
+}
+
+fn push(binding: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    binding.push(tag);
+    binding.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    binding.extend_from_slice(value);
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_binding() {
+        let binder = ContextBinder::new(Branka::new(&[4u8; 32], 0));
+        let client = RequestContext {
+            ip: Some("203.0.113.7".parse().unwrap()),
+            user_agent: Some("curl/8.0"),
+            ..Default::default()
+        };
+        let token = binder.encode(b"user:1", &client).unwrap();
+
+        let same_network = RequestContext {
+            ip: Some("203.0.113.200".parse().unwrap()),
+            ..client
+        };
+        assert_eq!(binder.decode(&token, &same_network).unwrap(), b"user:1");
+
+        let elsewhere = RequestContext {
+            ip: Some("198.51.100.7".parse().unwrap()),
+            ..client
+        };
+        assert_eq!(
+            binder.decode(&token, &elsewhere),
+            Err(BrankaError::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_masked() {
+        assert_eq!(masked(&[203, 0, 113, 7], 24), [203, 0, 113, 0, 24]);
+        assert_eq!(masked(&[255, 255], 12), [255, 240, 12]);
+        assert_eq!(masked(&[1, 2], 0), [0, 0, 0]);
+    }
+}
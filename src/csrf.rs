@@ -0,0 +1,79 @@
+use alloc::string::String;
+
+use subtle::ConstantTimeEq;
+
+use crate::{Branka, BrankaError};
+
+// Anti-CSRF tokens bound to a session.
+// The session fingerprint (session id, or any stable per-session value) is
+// sealed inside the token, so a token minted for one session is rejected for
+// any other. Use a short TTL on the underlying Branka instance.
+pub struct Csrf {
+    branka: Branka,
+}
+
+impl Csrf {
+    pub fn new(branka: Branka) -> Csrf {
+        Csrf { branka }
+    }
+
+    // Mint a CSRF token for the given session fingerprint.
+    pub fn mint(&self, session: &[u8]) -> String {
+        self.branka.encode(session)
+    }
+
+    // Check that a token was minted for this session and is not expired.
+    pub fn verify(&self, token: &str, session: &[u8]) -> Result<(), BrankaError> {
+        let bound = self.branka.decode(token)?;
+        if bool::from(bound.ct_eq(session)) {
+            Ok(())
+        } else {
+            Err(BrankaError::CsrfMismatch)
+        }
+    }
+
+    // Double-submit-cookie check: the token sent in the cookie and the one
+    // sent in the header/form field must be identical and valid for the session.
+    pub fn verify_double_submit(
+        &self,
+        cookie: &str,
+        submitted: &str,
+        session: &[u8],
+    ) -> Result<(), BrankaError> {
+        if !bool::from(cookie.as_bytes().ct_eq(submitted.as_bytes())) {
+            return Err(BrankaError::CsrfMismatch);
+        }
+        self.verify(submitted, session)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csrf_bound_to_session() {
+        let csrf = Csrf::new(Branka::new(&[1u8; 32], 600));
+        let token = csrf.mint(b"session-a");
+
+        assert!(csrf.verify(&token, b"session-a").is_ok());
+        assert!(matches!(
+            csrf.verify(&token, b"session-b"),
+            Err(BrankaError::CsrfMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_csrf_double_submit() {
+        let csrf = Csrf::new(Branka::new(&[1u8; 32], 600));
+        let token = csrf.mint(b"session-a");
+        let other = csrf.mint(b"session-a");
+
+        assert!(csrf
+            .verify_double_submit(&token, &token, b"session-a")
+            .is_ok());
+        assert!(csrf
+            .verify_double_submit(&token, &other, b"session-a")
+            .is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::string::String;
+use core::marker::PhantomData;
+
+use crate::{BrankaError, Compact, Payload};
+
+// Default size budget of a cursor token, in characters.
+pub const DEFAULT_CURSOR_LEN: usize = 128;
+
+// Opaque pagination cursors carrying a `T` (offset, sort key, filters):
+// compact tokens under a fixed size budget, so cursors stay short enough
+// for query strings and clients can neither read nor forge them.
+pub struct Cursor<T> {
+    compact: Compact,
+    max_len: usize,
+    state: PhantomData<fn() -> T>,
+}
+
+impl<T: Payload> Cursor<T> {
+    // Use an untimed Compact (`without_timestamp`) for cursors that should
+    // never expire.
+    pub fn new(compact: Compact) -> Cursor<T> {
+        Cursor {
+            compact,
+            max_len: DEFAULT_CURSOR_LEN,
+            state: PhantomData,
+        }
+    }
+
+    // Set the size budget, in characters.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    // Fails with TokenTooLong when the cursor would exceed the budget.
+    pub fn encode(&self, state: &T) -> Result<String, BrankaError> {
+        let token = self.compact.encode(&state.to_payload())?;
+        if token.len() > self.max_len {
+            return Err(BrankaError::TokenTooLong);
+        }
+        Ok(token)
+    }
+
+    pub fn decode(&self, cursor: &str) -> Result<T, BrankaError> {
+        if cursor.len() > self.max_len {
+            return Err(BrankaError::TokenTooLong);
+        }
+        T::from_payload(&self.compact.decode(cursor)?)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_budget() {
+        let cursor = Cursor::<u64>::new(Compact::new(&[2u8; 32], 0).without_timestamp());
+        let token = cursor.encode(&40).unwrap();
+        assert!(token.len() <= DEFAULT_CURSOR_LEN);
+        assert_eq!(cursor.decode(&token).unwrap(), 40);
+
+        let names = Cursor::<String>::new(Compact::new(&[2u8; 32], 0)).max_len(50);
+        assert_eq!(
+            names.encode(&"x".repeat(20)),
+            Err(BrankaError::TokenTooLong)
+        );
+        assert_eq!(
+            names.decode(&"A".repeat(51)),
+            Err(BrankaError::TokenTooLong)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::{BrankaError, RevocationStore, TokenId};
+
+// Persistent revocation list backed by an append-only log file.
+// Each line is `<hex token id> <expires_at>`. The log is loaded on open and
+// rewritten without expired entries by `compact`. Failed appends are
+// reported as StoreFailure and leave the entry out of the in-memory list.
+pub struct FileDenyList {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+struct State {
+    entries: HashMap<TokenId, u32>,
+    log: File,
+}
+
+impl FileDenyList {
+    // Open (or create) the deny-list at `path`. Malformed lines, such as a
+    // partial write from a crash, are skipped; a final line left without its
+    // newline is terminated, so the next append starts on a line of its own.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<FileDenyList> {
+        let path = path.as_ref().to_path_buf();
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .read(true)
+            .open(&path)?;
+
+        let mut entries = HashMap::new();
+        let mut reader = BufReader::new(&log);
+        let mut line = Vec::new();
+        let mut torn = false;
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            torn = line.last() != Some(&b'\n');
+            let parsed = std::str::from_utf8(&line)
+                .ok()
+                .and_then(|line| parse_line(line.trim_end()));
+            if let Some((id, expires_at)) = parsed {
+                entries.insert(id, expires_at);
+            }
+            line.clear();
+        }
+        if torn {
+            (&log).write_all(b"\n")?;
+        }
+
+        Ok(FileDenyList {
+            path,
+            state: Mutex::new(State { entries, log }),
+        })
+    }
+
+    // Rewrite the log keeping only entries that have not expired at `now`.
+    pub fn compact(&self, now: u32) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|_, expires_at| *expires_at >= now);
+
+        let tmp = self.path.with_extension("compact");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            for (id, expires_at) in state.entries.iter() {
+                write_line(&mut out, id, *expires_at)?;
+            }
+            out.into_inner()?.sync_all()?;
+        }
+        std::fs::rename(&tmp, &self.path)?;
+        state.log = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl RevocationStore for FileDenyList {
+    fn revoke(&self, id: &TokenId, expires_at: u32, now: u32) -> Result<(), BrankaError> {
+        let mut state = self.state.lock().unwrap();
+        // Expired entries stay in the log until the next compaction.
+        state.entries.retain(|_, expires_at| *expires_at >= now);
+        write_line(&mut state.log, id, expires_at).map_err(|_| BrankaError::StoreFailure)?;
+        state.entries.insert(*id, expires_at);
+        Ok(())
+    }
+
+    fn is_revoked(&self, id: &TokenId) -> bool {
+        self.state.lock().unwrap().entries.contains_key(id)
+    }
+}
+
+fn write_line<W: Write>(out: &mut W, id: &TokenId, expires_at: u32) -> io::Result<()> {
+    let mut line = String::with_capacity(2 * id.len() + 12);
+    for b in id {
+        line.push_str(&format!("{:02x}", b));
+    }
+    line.push_str(&format!(" {}\n", expires_at));
+    out.write_all(line.as_bytes())
+}
+
+fn parse_line(line: &str) -> Option<(TokenId, u32)> {
+    let (hex, expires_at) = line.split_once(' ')?;
+    if hex.len() != 48 {
+        return None;
+    }
+    let mut id = [0u8; 24];
+    for (i, b) in id.iter_mut().enumerate() {
+        *b = u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok()?;
+    }
+    Some((id, expires_at.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deny_list_persists_and_compacts() {
+        let path = std::env::temp_dir().join(format!("branka-denylist-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let list = FileDenyList::open(&path).unwrap();
+            list.revoke(&[1u8; 24], 0, 0).unwrap();
+            list.revoke(&[2u8; 24], u32::MAX, 0).unwrap();
+        }
+
+        let list = FileDenyList::open(&path).unwrap();
+        assert!(list.is_revoked(&[1u8; 24]));
+        assert!(list.is_revoked(&[2u8; 24]));
+
+        list.compact(1).unwrap();
+        assert!(!list.is_revoked(&[1u8; 24]));
+        list.revoke(&[3u8; 24], u32::MAX, 0).unwrap();
+        drop(list);
+
+        let list = FileDenyList::open(&path).unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.is_revoked(&[3u8; 24]));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_deny_list_recovers_torn_line() {
+        let path =
+            std::env::temp_dir().join(format!("branka-denylist-torn-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let list = FileDenyList::open(&path).unwrap();
+            list.revoke(&[1u8; 24], u32::MAX, 0).unwrap();
+            list.revoke(&[2u8; 24], u32::MAX, 0).unwrap();
+        }
+        // A crash in the middle of the second append.
+        let len = std::fs::metadata(&path).unwrap().len();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 20)
+            .unwrap();
+
+        let list = FileDenyList::open(&path).unwrap();
+        assert!(!list.is_revoked(&[2u8; 24]));
+        list.revoke(&[3u8; 24], u32::MAX, 0).unwrap();
+        drop(list);
+
+        let list = FileDenyList::open(&path).unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.is_revoked(&[1u8; 24]));
+        assert!(list.is_revoked(&[3u8; 24]));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{base62, Branka, BrankaError, TOKEN_OVERHEAD};
+
+// A token with its Poly1305 tag stored apart from the body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetachedToken {
+    // Base62 of Version || Timestamp || Nonce || Ciphertext.
+    pub body: String,
+    pub tag: [u8; 16],
+}
+
+impl Branka {
+    // Encode `data`, returning the tag separately from the token body, for
+    // protocols that transmit or store the MAC out of band.
+    pub fn encode_detached(&self, data: &[u8]) -> Result<DetachedToken, BrankaError> {
+        let mut buf = vec![0u8; data.len() + TOKEN_OVERHEAD];
+        self.encode_to_slice(data, &mut buf)?;
+
+        let tag = buf[buf.len() - 16..].try_into().unwrap();
+        let mut body = String::new();
+        let body_len = buf.len() - 16;
+        base62::encode_into(&mut buf[..body_len], &mut body);
+        Ok(DetachedToken { body, tag })
+    }
+
+    // Verify and decode a token body against its separately kept tag.
+    pub fn decode_detached(&self, body: &str, tag: &[u8; 16]) -> Result<Vec<u8>, BrankaError> {
+        if body.len() > self.max_token_len {
+            self.audit_decoded(Err(&BrankaError::TokenTooLong));
+            return Err(BrankaError::TokenTooLong);
+        }
+        let mut buf = Vec::new();
+        if base62::decode_into(body, &mut buf).is_err() {
+            self.audit_decoded(Err(&BrankaError::InvalidBase62));
+            return Err(BrankaError::InvalidBase62);
+        }
+        buf.extend_from_slice(tag);
+
+        let mut payload = vec![0u8; buf.len().saturating_sub(TOKEN_OVERHEAD)];
+        let len = self.decode_to_slice(&buf, &mut payload)?;
+        payload.truncate(len);
+        Ok(payload)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detached_round_trip() {
+        let branka = Branka::new(&[6u8; 32], 0);
+        let token = branka.encode_detached(b"blob").unwrap();
+        assert_eq!(
+            branka.decode_detached(&token.body, &token.tag).unwrap(),
+            b"blob"
+        );
+
+        let mut tag = token.tag;
+        tag[0] ^= 1;
+        assert_eq!(
+            branka.decode_detached(&token.body, &tag),
+            Err(BrankaError::InvalidData)
+        );
+    }
+}
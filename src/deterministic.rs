@@ -0,0 +1,47 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use chacha20poly1305::XNonce;
+
+// Frozen clock used by deterministic instances, chosen to match the
+// timestamp of the reference branca test vectors.
+pub const DETERMINISTIC_TIMESTAMP: u32 = 123206400;
+
+// Nonce source for Branka::new_deterministic.
+// Nonces are `seed || counter || 0..`, unique per instance but predictable.
+pub(crate) struct Deterministic {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl Deterministic {
+    pub fn new(seed: u64) -> Deterministic {
+        Deterministic {
+            seed,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    pub fn next_nonce(&self) -> XNonce {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+        let mut nonce = XNonce::default();
+        nonce[..8].copy_from_slice(&self.seed.to_be_bytes());
+        nonce[8..16].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Branka;
+
+    #[test]
+    fn test_deterministic_tokens_reproducible() {
+        let a = Branka::new_deterministic(&[1u8; 32], 3600, 42);
+        let b = Branka::new_deterministic(&[1u8; 32], 3600, 42);
+
+        let first = a.encode(b"payload");
+        assert_eq!(first, b.encode(b"payload"));
+        assert_ne!(first, a.encode(b"payload"));
+        assert_eq!(a.decode(&first).unwrap(), b"payload");
+    }
+}
@@ -0,0 +1,174 @@
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+
+use zeroize::Zeroizing;
+
+use crate::{base64url, Branka};
+
+// Why a key could not be loaded from the environment. Never includes the
+// variable's value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    NotSet { var: String },
+    InvalidEncoding { var: String },
+    WrongLength { var: String, len: usize },
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::NotSet { var } => write!(f, "{} is not set", var),
+            KeyError::InvalidEncoding { var } => {
+                write!(f, "{} is not a key in hex or base64", var)
+            }
+            KeyError::WrongLength { var, len } => {
+                write!(f, "{} holds a {} bytes key, expected 32", var, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+impl Branka {
+    // Create a Branka instance from the key in environment variable `var`,
+    // as 64 hex digits or base64 (standard or URL-safe, padded or not).
+    // Copies of the key made while decoding are zeroed; the process
+    // environment itself is left as is.
+    pub fn from_env(var: &str, ttl: u32) -> Result<Branka, KeyError> {
+        from_lookup(var, ttl, |var| env::var_os(var), decode_key)
+    }
+
+    // from_env for a variable holding the 32 key bytes themselves. Kept
+    // separate so that a short hex key or a passphrase that happens to be 32
+    // characters long is never taken for key bytes.
+    pub fn from_env_raw(var: &str, ttl: u32) -> Result<Branka, KeyError> {
+        from_lookup(var, ttl, |var| env::var_os(var), raw_key)
+    }
+}
+
+// from_env with the variable read through `lookup` and its value turned
+// into key bytes by `decode`.
+fn from_lookup(
+    var: &str,
+    ttl: u32,
+    lookup: impl FnOnce(&str) -> Option<OsString>,
+    decode: fn(&[u8]) -> Option<Zeroizing<Vec<u8>>>,
+) -> Result<Branka, KeyError> {
+    let text = Zeroizing::new(
+        lookup(var)
+            .ok_or_else(|| KeyError::NotSet {
+                var: var.to_string(),
+            })?
+            .into_encoded_bytes(),
+    );
+    let key = decode(&text).ok_or_else(|| KeyError::InvalidEncoding {
+        var: var.to_string(),
+    })?;
+    if key.len() != 32 {
+        return Err(KeyError::WrongLength {
+            var: var.to_string(),
+            len: key.len(),
+        });
+    }
+    Ok(Branka::new(&key, ttl))
+}
+
+// Text made only of hex digits is hex, anything else base64; None if it
+// is neither. The length is checked by the caller.
+//
+// Every buffer holding key material is sized upfront, so that it is never
+// reallocated (leaving an unwiped copy behind), and zeroed when dropped.
+fn decode_key(text: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    let text = text.trim_ascii();
+    if text.iter().all(u8::is_ascii_hexdigit) {
+        return decode_hex(text);
+    }
+
+    // Map standard base64 onto the URL-safe alphabet.
+    let mut url_safe = Zeroizing::new(Vec::with_capacity(text.len()));
+    url_safe.extend(text.iter().take_while(|&&c| c != b'=').map(|&c| match c {
+        b'+' => b'-',
+        b'/' => b'_',
+        c => c,
+    }));
+    let text = core::str::from_utf8(&url_safe).ok()?;
+    let mut key = Zeroizing::new(Vec::new());
+    base64url::decode_into(text, &mut key).ok()?;
+    Some(key)
+}
+
+fn raw_key(text: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    Some(Zeroizing::new(text.to_vec()))
+}
+
+fn decode_hex(text: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    let mut key = Zeroizing::new(Vec::with_capacity(text.len() / 2));
+    for pair in text.chunks(2) {
+        let digits = core::str::from_utf8(pair).ok()?;
+        key.push(u8::from_str_radix(digits, 16).ok()?);
+    }
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_key_encodings() {
+        let key: Vec<u8> = (0..32).collect();
+        let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(*decode_key(hex.as_bytes()).unwrap(), key);
+        assert_eq!(
+            *decode_key(b"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=\n").unwrap(),
+            key
+        );
+        assert_eq!(
+            *decode_key(b"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8").unwrap(),
+            key
+        );
+        assert!(decode_key(b"not a key!").is_none());
+        assert!(decode_key(b"00f").is_none());
+    }
+
+    #[test]
+    fn test_from_env_errors() {
+        let missing = from_lookup("BRANKA_KEY", 0, |_| None, decode_key).unwrap_err();
+        assert_eq!(missing.to_string(), "BRANKA_KEY is not set");
+
+        let lookup = |value: &str| {
+            let value = OsString::from(value);
+            from_lookup("BRANKA_KEY", 0, |_| Some(value), decode_key)
+        };
+        assert_eq!(
+            lookup("00ff").unwrap_err(),
+            KeyError::WrongLength {
+                var: "BRANKA_KEY".to_string(),
+                len: 2
+            }
+        );
+        // 128-bit hex key, and a 32 characters passphrase.
+        assert_eq!(
+            lookup(&"ab".repeat(16)).unwrap_err(),
+            KeyError::WrongLength {
+                var: "BRANKA_KEY".to_string(),
+                len: 16
+            }
+        );
+        assert_eq!(
+            lookup("correct horse battery staple!!!!").unwrap_err(),
+            KeyError::InvalidEncoding {
+                var: "BRANKA_KEY".to_string()
+            }
+        );
+        assert!(lookup(&"ab".repeat(32)).is_ok());
+
+        let raw = |_: &str| Some(OsString::from("k".repeat(32)));
+        assert!(from_lookup("BRANKA_KEY", 0, raw, raw_key).is_ok());
+    }
+}
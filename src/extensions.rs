@@ -0,0 +1,304 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use byteorder::{BigEndian, ByteOrder};
+use chacha20poly1305::{aead::generic_array::GenericArray, AeadInPlace, XNonce};
+use zeroize::Zeroize;
+
+use crate::{base62, Branka, BrankaError};
+
+// Version 2 magic byte. v2 tokens are not Branca tokens: other
+// implementations reject them on the version byte.
+pub(crate) const VERSION_V2: u8 = 0xBB;
+
+// Version || Timestamp || Nonce || Extensions length, and the Poly1305 tag.
+const V2_HEADER: usize = 29 + 2;
+const V2_OVERHEAD: usize = V2_HEADER + 16;
+
+// Extension types. 0 is reserved; types from 0x80 up are free for
+// application use.
+pub const EXT_KEY_ID: u8 = 1;
+pub const EXT_AUDIENCE: u8 = 2;
+pub const EXT_PURPOSE: u8 = 3;
+pub const EXT_CHUNK_INDEX: u8 = 4;
+pub const EXT_TRACE_CONTEXT: u8 = 5;
+
+// Header extensions of a v2 token: a list of type || length || value
+// entries (one byte each for type and length), stored in the clear after the
+// nonce and authenticated as part of the header. New extension types need
+// no new version byte; decoders keep the entries they don't know about.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extensions {
+    entries: Vec<(u8, Vec<u8>)>,
+}
+
+impl Extensions {
+    pub fn new() -> Extensions {
+        Extensions::default()
+    }
+
+    // Set extension `kind` to `value`, replacing any previous value.
+    // Panics if `value` is longer than 255 bytes.
+    pub fn with(mut self, kind: u8, value: &[u8]) -> Self {
+        assert!(value.len() <= u8::MAX as usize, "extension value too long");
+        self.entries.retain(|(k, _)| *k != kind);
+        self.entries.push((kind, value.to_vec()));
+        self
+    }
+
+    pub fn with_key_id(self, key_id: &[u8]) -> Self {
+        self.with(EXT_KEY_ID, key_id)
+    }
+
+    pub fn with_audience(self, audience: &str) -> Self {
+        self.with(EXT_AUDIENCE, audience.as_bytes())
+    }
+
+    pub fn with_purpose(self, purpose: &str) -> Self {
+        self.with(EXT_PURPOSE, purpose.as_bytes())
+    }
+
+    pub fn with_chunk_index(self, index: u32) -> Self {
+        self.with(EXT_CHUNK_INDEX, &index.to_be_bytes())
+    }
+
+    pub fn get(&self, kind: u8) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    pub fn key_id(&self) -> Option<&[u8]> {
+        self.get(EXT_KEY_ID)
+    }
+
+    pub fn audience(&self) -> Option<&str> {
+        self.get(EXT_AUDIENCE)
+            .and_then(|value| core::str::from_utf8(value).ok())
+    }
+
+    pub fn purpose(&self) -> Option<&str> {
+        self.get(EXT_PURPOSE)
+            .and_then(|value| core::str::from_utf8(value).ok())
+    }
+
+    pub fn chunk_index(&self) -> Option<u32> {
+        self.get(EXT_CHUNK_INDEX)
+            .filter(|value| value.len() == 4)
+            .map(BigEndian::read_u32)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        for (kind, value) in &self.entries {
+            out.push(*kind);
+            out.push(value.len() as u8);
+            out.extend_from_slice(value);
+        }
+    }
+
+    fn parse(mut bytes: &[u8]) -> Option<Extensions> {
+        let mut entries = Vec::new();
+        while let [kind, len, rest @ ..] = bytes {
+            let len = usize::from(*len);
+            if rest.len() < len {
+                return None;
+            }
+            entries.push((*kind, rest[..len].to_vec()));
+            bytes = &rest[len..];
+        }
+        bytes.is_empty().then_some(Extensions { entries })
+    }
+}
+
+impl Branka {
+    // Encode a v2 token carrying `extensions` in its header.
+    pub fn encode_v2(&self, data: &[u8], extensions: &Extensions) -> Result<String, BrankaError> {
+        let mut ext = Vec::new();
+        extensions.write(&mut ext);
+        let ext_len = u16::try_from(ext.len()).map_err(|_| BrankaError::InvalidPayload)?;
+
+        let nonce = self.next_nonce()?;
+        let timestamp = self.now();
+        let header_len = V2_HEADER + ext.len();
+        let mut buf = Vec::with_capacity(header_len + data.len() + 16);
+        buf.push(VERSION_V2);
+        buf.extend_from_slice(&timestamp.to_be_bytes());
+        buf.extend_from_slice(&nonce);
+        buf.extend_from_slice(&ext_len.to_be_bytes());
+        buf.extend_from_slice(&ext);
+        buf.extend_from_slice(data);
+
+        let (header, payload) = buf.split_at_mut(header_len);
+        let sign = self
+            .cipher
+            .encrypt_in_place_detached(&nonce, header, payload)
+            .unwrap();
+        buf.extend_from_slice(&sign);
+
+        let mut token = String::new();
+        base62::encode_into(&mut buf, &mut token);
+        self.audit_encoded(timestamp, data.len());
+        Ok(token)
+    }
+
+    // Decode a v2 token, returning its payload and header extensions.
+    // Checking the extensions (audience, purpose, ...) is up to the caller.
+    pub fn decode_v2(&self, data: &str) -> Result<(Vec<u8>, Extensions), BrankaError> {
+        let decoded = self.decode_v2_full(data)?;
+        Ok((decoded.payload, decoded.extensions))
+    }
+
+    // Like decode_v2, also returning the token timestamp.
+    pub(crate) fn decode_v2_full(&self, data: &str) -> Result<DecodedV2, BrankaError> {
+        let result = self.decode_v2_inner(data);
+        self.audit_decoded(
+            result
+                .as_ref()
+                .map(|decoded| (decoded.timestamp, decoded.payload.len())),
+        );
+        result
+    }
+
+    // As for v1 tokens, malformed, wrong-key and expired tokens all go
+    // through the AEAD verification before any check is allowed to fail.
+    fn decode_v2_inner(&self, data: &str) -> Result<DecodedV2, BrankaError> {
+        let mut buf = self.unpack_v2(data)?;
+
+        // Malformed tokens are verified as an empty (zeroed) token instead.
+        let shape = if buf.len() < V2_OVERHEAD {
+            Err(BrankaError::InvalidDataLength)
+        } else if buf[0] != VERSION_V2 {
+            Err(BrankaError::InvalidVersion)
+        } else {
+            header_len(&buf).ok_or(BrankaError::InvalidDataLength)
+        };
+        if shape.is_err() {
+            buf.clear();
+            buf.resize(V2_OVERHEAD, 0);
+        }
+        let header_len = *shape.as_ref().unwrap_or(&V2_HEADER);
+
+        let sign_at = buf.len() - 16;
+        let (rest, sign) = buf.split_at_mut(sign_at);
+        let (header, payload) = rest.split_at_mut(header_len);
+        let authentic = self
+            .cipher
+            .decrypt_in_place_detached(
+                XNonce::from_slice(&header[5..29]),
+                header,
+                payload,
+                GenericArray::from_slice(sign),
+            )
+            .is_ok();
+        let timestamp = BigEndian::read_u32(&header[1..5]);
+
+        let checked = if let Err(err) = shape {
+            Err(err)
+        } else if !authentic {
+            Err(BrankaError::InvalidData)
+        } else if self.expires_at(timestamp) < self.now() {
+            Err(BrankaError::Expired)
+        } else {
+            Extensions::parse(&header[V2_HEADER..]).ok_or(BrankaError::InvalidDataLength)
+        };
+        let extensions = match checked {
+            Ok(extensions) => extensions,
+            Err(err) => {
+                // An expired token has been decrypted before being rejected.
+                buf.zeroize();
+                return Err(err);
+            }
+        };
+        Ok(DecodedV2 {
+            timestamp,
+            payload: payload.to_vec(),
+            extensions,
+        })
+    }
+
+    // Read the extensions of a v2 token WITHOUT verifying it, e.g. to pick
+    // the key to decode it with. Never trust the result otherwise.
+    pub(crate) fn peek_extensions(&self, data: &str) -> Result<Extensions, BrankaError> {
+        let buf = self.unpack_v2(data)?;
+        if buf.first() != Some(&VERSION_V2) {
+            return Err(BrankaError::InvalidVersion);
+        }
+        if buf.len() < V2_OVERHEAD {
+            return Err(BrankaError::InvalidDataLength);
+        }
+        let header_len = header_len(&buf).ok_or(BrankaError::InvalidDataLength)?;
+        Extensions::parse(&buf[V2_HEADER..header_len]).ok_or(BrankaError::InvalidDataLength)
+    }
+
+    fn unpack_v2(&self, data: &str) -> Result<Vec<u8>, BrankaError> {
+        if data.len() > self.max_token_len {
+            return Err(BrankaError::TokenTooLong);
+        }
+        let mut buf = Vec::new();
+        base62::decode_into(data, &mut buf).map_err(|_| BrankaError::InvalidBase62)?;
+        Ok(buf)
+    }
+}
+
+// A verified v2 token.
+pub(crate) struct DecodedV2 {
+    pub timestamp: u32,
+    pub payload: Vec<u8>,
+    pub extensions: Extensions,
+}
+
+// Length of the header of the binary v2 token `buf`, extensions included,
+// or None if `buf` is too short to hold it and a tag.
+fn header_len(buf: &[u8]) -> Option<usize> {
+    let ext_len = buf.get(29..V2_HEADER).map(BigEndian::read_u16)?;
+    let header_len = V2_HEADER + usize::from(ext_len);
+    (buf.len() >= header_len + 16).then_some(header_len)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v2_extensions_roundtrip() {
+        let branka = Branka::new(&[6u8; 32], 0);
+        let extensions = Extensions::new()
+            .with_key_id(b"k1")
+            .with_audience("billing")
+            .with_chunk_index(3)
+            .with(0x80, b"custom");
+
+        let token = branka.encode_v2(b"data", &extensions).unwrap();
+        let (payload, decoded) = branka.decode_v2(&token).unwrap();
+        assert_eq!(payload, b"data");
+        assert_eq!(decoded, extensions);
+        assert_eq!(decoded.audience(), Some("billing"));
+        assert_eq!(decoded.chunk_index(), Some(3));
+        assert_eq!(decoded.purpose(), None);
+
+        assert_eq!(branka.decode(&token), Err(BrankaError::InvalidVersion));
+        assert_eq!(
+            branka.decode_v2(&branka.encode(b"data")),
+            Err(BrankaError::InvalidVersion)
+        );
+    }
+
+    #[test]
+    fn test_v2_extensions_are_authenticated() {
+        let branka = Branka::new(&[6u8; 32], 0);
+        let token = branka
+            .encode_v2(b"data", &Extensions::new().with_purpose("login"))
+            .unwrap();
+
+        let mut raw = base_x::decode(crate::BASE62, &token).unwrap();
+        // First byte of the purpose value.
+        raw[V2_HEADER + 2] ^= 0x20;
+        let tampered = base_x::encode(crate::BASE62, &raw);
+        assert_eq!(branka.decode_v2(&tampered), Err(BrankaError::InvalidData));
+    }
+}
@@ -0,0 +1,175 @@
+// C API. Build a C library with
+// `cargo rustc --release --features ffi --crate-type cdylib` (or staticlib);
+// the matching header is include/branka.h, generated with cbindgen.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+use crate::{Branka, BrankaError};
+
+pub const BRANKA_OK: c_int = 0;
+pub const BRANKA_ERR_NULL: c_int = -1;
+pub const BRANKA_ERR_INVALID_TOKEN: c_int = -2;
+pub const BRANKA_ERR_EXPIRED: c_int = -3;
+pub const BRANKA_ERR_RNG: c_int = -4;
+
+/// Create a Branka instance from a 32 bytes key.
+/// Returns NULL if `key` is NULL or `key_len` is not 32.
+/// The instance must be released with branka_free.
+///
+/// # Safety
+/// `key` must point to `key_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn branka_new(key: *const u8, key_len: usize, ttl: u32) -> *mut Branka {
+    if key.is_null() || key_len != 32 {
+        return ptr::null_mut();
+    }
+    let key = std::slice::from_raw_parts(key, key_len);
+    Box::into_raw(Box::new(Branka::new(key, ttl)))
+}
+
+/// Release an instance created by branka_new. NULL is ignored.
+///
+/// # Safety
+/// `branka` must come from branka_new and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn branka_free(branka: *mut Branka) {
+    if !branka.is_null() {
+        drop(Box::from_raw(branka));
+    }
+}
+
+/// Encode `data` into a NUL-terminated token written to `*token`.
+/// The token must be released with branka_string_free.
+///
+/// # Safety
+/// `branka` must come from branka_new, `data` must point to `data_len`
+/// readable bytes (it may be NULL when `data_len` is 0) and `token` must be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn branka_encode(
+    branka: *const Branka,
+    data: *const u8,
+    data_len: usize,
+    token: *mut *mut c_char,
+) -> c_int {
+    if branka.is_null() || token.is_null() || (data.is_null() && data_len > 0) {
+        return BRANKA_ERR_NULL;
+    }
+    let data = if data_len == 0 {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts(data, data_len)
+    };
+
+    match (*branka).try_encode(data) {
+        // Base62 never contains NUL bytes.
+        Ok(encoded) => {
+            *token = CString::new(encoded).unwrap().into_raw();
+            BRANKA_OK
+        }
+        Err(err) => error_code(&err),
+    }
+}
+
+/// Decode a NUL-terminated token. On success the payload is written to
+/// `*data`/`*data_len` and must be released with branka_bytes_free.
+///
+/// # Safety
+/// `branka` must come from branka_new, `token` must be a NUL-terminated string
+/// and `data`/`data_len` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn branka_decode(
+    branka: *const Branka,
+    token: *const c_char,
+    data: *mut *mut u8,
+    data_len: *mut usize,
+) -> c_int {
+    if branka.is_null() || token.is_null() || data.is_null() || data_len.is_null() {
+        return BRANKA_ERR_NULL;
+    }
+    let token = match CStr::from_ptr(token).to_str() {
+        Ok(token) => token,
+        Err(_) => return BRANKA_ERR_INVALID_TOKEN,
+    };
+
+    match (*branka).decode(token) {
+        Ok(payload) => {
+            let payload = payload.into_boxed_slice();
+            *data_len = payload.len();
+            *data = Box::into_raw(payload) as *mut u8;
+            BRANKA_OK
+        }
+        Err(err) => error_code(&err),
+    }
+}
+
+/// Release a token returned by branka_encode. NULL is ignored.
+///
+/// # Safety
+/// `token` must come from branka_encode and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn branka_string_free(token: *mut c_char) {
+    if !token.is_null() {
+        drop(CString::from_raw(token));
+    }
+}
+
+/// Release a payload returned by branka_decode. NULL is ignored.
+///
+/// # Safety
+/// `data`/`data_len` must come from branka_decode and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn branka_bytes_free(data: *mut u8, data_len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, data_len)));
+    }
+}
+
+fn error_code(err: &BrankaError) -> c_int {
+    match err {
+        BrankaError::Expired => BRANKA_ERR_EXPIRED,
+        BrankaError::RngFailure => BRANKA_ERR_RNG,
+        _ => BRANKA_ERR_INVALID_TOKEN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_round_trip() {
+        unsafe {
+            assert!(branka_new([0u8; 16].as_ptr(), 16, 0).is_null());
+
+            let branka = branka_new([7u8; 32].as_ptr(), 32, 0);
+            assert!(!branka.is_null());
+
+            let mut token = ptr::null_mut();
+            let data = b"from C";
+            assert_eq!(
+                branka_encode(branka, data.as_ptr(), data.len(), &mut token),
+                BRANKA_OK
+            );
+
+            let mut out = ptr::null_mut();
+            let mut out_len = 0;
+            assert_eq!(
+                branka_decode(branka, token, &mut out, &mut out_len),
+                BRANKA_OK
+            );
+            assert_eq!(std::slice::from_raw_parts(out, out_len), data);
+
+            assert_eq!(
+                branka_decode(branka, c"nope".as_ptr(), &mut out, &mut out_len),
+                BRANKA_ERR_INVALID_TOKEN
+            );
+
+            branka_bytes_free(out, out_len);
+            branka_string_free(token);
+            branka_free(branka);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{BrankaError, Keyring};
+
+// Encrypts individual database fields (emails, third party credentials)
+// with the application's keyring, so they rotate with the other keys.
+//
+// Each value is bound to its location, `field` (e.g. `users.email`) and the
+// row's primary key, as associated data: a ciphertext copied into another
+// column or row fails to decrypt. Encryption is randomized, so encrypted
+// fields cannot be compared for equality; keep a separate lookup column if
+// the field must be searchable.
+//
+// Fields do not expire: build the keyring from instances with a TTL of 0.
+pub struct FieldCipher {
+    keys: Keyring,
+}
+
+impl FieldCipher {
+    pub fn new(keys: Keyring) -> FieldCipher {
+        FieldCipher { keys }
+    }
+
+    pub fn encrypt(&self, field: &str, row: &[u8], value: &[u8]) -> Result<String, BrankaError> {
+        self.keys.encode_aad(value, &location(field, row))
+    }
+
+    // Values encrypted under any key of the keyring decrypt; a value moved
+    // to another field or row fails with InvalidData.
+    pub fn decrypt(&self, field: &str, row: &[u8], stored: &str) -> Result<Vec<u8>, BrankaError> {
+        self.keys.decode_aad(stored, &location(field, row))
+    }
+}
+
+// Field length || Field || Row
+fn location(field: &str, row: &[u8]) -> Vec<u8> {
+    let mut location = Vec::with_capacity(4 + field.len() + row.len());
+    location.extend_from_slice(&(field.len() as u32).to_be_bytes());
+    location.extend_from_slice(field.as_bytes());
+    location.extend_from_slice(row);
+    location
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Branka;
+
+    #[test]
+    fn test_field_cipher() {
+        let old = FieldCipher::new(Keyring::new(Branka::new(&[1u8; 32], 0)));
+        let stored = old
+            .encrypt("users.email", b"42", b"ada@example.com")
+            .unwrap();
+
+        let keys =
+            Keyring::new(Branka::new(&[2u8; 32], 0)).with_previous(Branka::new(&[1u8; 32], 0));
+        let cipher = FieldCipher::new(keys);
+        assert_eq!(
+            cipher.decrypt("users.email", b"42", &stored).unwrap(),
+            b"ada@example.com"
+        );
+        assert_eq!(
+            cipher.decrypt("users.email", b"43", &stored),
+            Err(BrankaError::InvalidData)
+        );
+        assert_eq!(
+            cipher.decrypt("users.phone", b"42", &stored),
+            Err(BrankaError::InvalidData)
+        );
+    }
+}
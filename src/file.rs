@@ -0,0 +1,131 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::stream::{io_error, ChunkSealer, Chunks, STREAM_HEADER};
+use crate::{Branka, DEFAULT_STREAM_CHUNK_SIZE, TOKEN_OVERHEAD};
+
+// Longest chunk token an encrypted file may contain.
+const MAX_CHUNK_TOKEN: usize = DEFAULT_STREAM_CHUNK_SIZE + STREAM_HEADER + TOKEN_OVERHEAD;
+
+impl Branka {
+    // Encrypt the file at `src` into `dst`, one chunk at a time.
+    // The output is the chunked stream format in binary: each chunk token is
+    // written raw (no base62), prefixed with its length as a u32 BE.
+    pub fn encrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(&self, src: P, dst: Q) -> io::Result<()> {
+        let mut chunks = Chunks::new(BufReader::new(File::open(src)?), DEFAULT_STREAM_CHUNK_SIZE);
+        write_atomically(dst.as_ref(), |out| {
+            let mut sealer = ChunkSealer::default();
+            let mut token = Vec::new();
+            while let Some((data, last)) = chunks.next_chunk()? {
+                let payload = sealer.payload(self, &data, last)?;
+                token.resize(payload.len() + TOKEN_OVERHEAD, 0);
+                self.encode_to_slice(&payload, &mut token)
+                    .map_err(io_error)?;
+                out.write_all(&(token.len() as u32).to_be_bytes())?;
+                out.write_all(&token)?;
+            }
+            Ok(())
+        })
+    }
+
+    // Decrypt a file written by encrypt_file. `dst` only appears once the
+    // whole file has been verified, so a tampered or truncated file never
+    // leaves partial plaintext behind.
+    // Chunks are checked against this instance's TTL, like any token; use a
+    // TTL of 0 for files kept at rest.
+    pub fn decrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(&self, src: P, dst: Q) -> io::Result<()> {
+        let mut input = BufReader::new(File::open(src)?);
+        write_atomically(dst.as_ref(), |out| {
+            let mut decoder = self.stream_decoder();
+            let mut token = Vec::with_capacity(MAX_CHUNK_TOKEN);
+            while !decoder.is_finished() {
+                let mut len = [0u8; 4];
+                input.read_exact(&mut len)?;
+                let len = u32::from_be_bytes(len) as usize;
+                if len > MAX_CHUNK_TOKEN {
+                    return Err(invalid_data("chunk too long"));
+                }
+
+                token.resize(len, 0);
+                input.read_exact(&mut token)?;
+                let mut payload = vec![0u8; len.saturating_sub(TOKEN_OVERHEAD)];
+                let n = self
+                    .decode_to_slice(&token, &mut payload)
+                    .map_err(io_error)?;
+                payload.truncate(n);
+                out.write_all(&decoder.accept(payload).map_err(io_error)?)?;
+            }
+            if input.read(&mut [0u8])? != 0 {
+                return Err(invalid_data("data after the last chunk"));
+            }
+            Ok(())
+        })
+    }
+}
+
+// Write `dst` through a temporary file, renamed into place only if `write`
+// succeeds.
+fn write_atomically(
+    dst: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let tmp = temporary_path(dst);
+    let result = File::create(&tmp).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        write(&mut out)?;
+        out.into_inner()?.sync_all()
+    });
+    match result {
+        Ok(()) => fs::rename(&tmp, dst),
+        Err(err) => {
+            let _ = fs::remove_file(&tmp);
+            Err(err)
+        }
+    }
+}
+
+fn temporary_path(dst: &Path) -> PathBuf {
+    let mut name = OsString::from(dst.as_os_str());
+    name.push(".branka-tmp");
+    PathBuf::from(name)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_round_trip() {
+        let dir = std::env::temp_dir();
+        let path = |name: &str| dir.join(format!("branka-file-{}-{}", std::process::id(), name));
+        let (plain, sealed, opened) = (path("plain"), path("sealed"), path("opened"));
+
+        let data: Vec<u8> = (0..=255)
+            .cycle()
+            .take(3 * DEFAULT_STREAM_CHUNK_SIZE + 7)
+            .collect();
+        fs::write(&plain, &data).unwrap();
+        let branka = Branka::new(&[5u8; 32], 0);
+        branka.encrypt_file(&plain, &sealed).unwrap();
+        branka.decrypt_file(&sealed, &opened).unwrap();
+        assert_eq!(fs::read(&opened).unwrap(), data);
+
+        // Drop the last chunk.
+        fs::remove_file(&opened).unwrap();
+        let mut contents = fs::read(&sealed).unwrap();
+        contents.truncate(contents.len() - (4 + 7 + STREAM_HEADER + TOKEN_OVERHEAD));
+        fs::write(&sealed, &contents).unwrap();
+        assert!(branka.decrypt_file(&sealed, &opened).is_err());
+        assert!(!opened.exists());
+
+        for path in [plain, sealed] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
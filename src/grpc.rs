@@ -0,0 +1,73 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Branka, BrankaError, TOKEN_OVERHEAD};
+
+// Metadata key for tokens. gRPC base64-encodes the values of keys ending in
+// `-bin` on the wire, so raw binary tokens go there instead of base62 ones,
+// which would be encoded twice.
+pub const GRPC_METADATA_KEY: &str = "branka-token-bin";
+
+// Largest binary token accepted in metadata. Servers commonly cap the whole
+// metadata at 8 KiB, and base64 grows the value by a third on the wire.
+pub const MAX_GRPC_TOKEN_LEN: usize = 4 * 1024;
+
+// Whether `key` is a valid gRPC binary metadata key: lowercase ASCII
+// letters, digits, `_`, `-` or `.`, ending in `-bin`.
+pub fn is_binary_metadata_key(key: &str) -> bool {
+    key.len() > 4
+        && key.ends_with("-bin")
+        && key
+            .bytes()
+            .all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.'))
+}
+
+impl Branka {
+    // Encode a raw binary token for a `-bin` metadata value.
+    // Fails with TokenTooLong above MAX_GRPC_TOKEN_LEN.
+    pub fn encode_grpc(&self, data: &[u8]) -> Result<Vec<u8>, BrankaError> {
+        let len = data.len() + TOKEN_OVERHEAD;
+        if len > MAX_GRPC_TOKEN_LEN {
+            return Err(BrankaError::TokenTooLong);
+        }
+        let mut token = vec![0u8; len];
+        self.encode_to_slice(data, &mut token)?;
+        Ok(token)
+    }
+
+    // Decode a raw binary token read from a `-bin` metadata value.
+    pub fn decode_grpc(&self, value: &[u8]) -> Result<Vec<u8>, BrankaError> {
+        if value.len() > MAX_GRPC_TOKEN_LEN {
+            return Err(BrankaError::TokenTooLong);
+        }
+        let mut payload = vec![0u8; value.len().saturating_sub(TOKEN_OVERHEAD)];
+        let len = self.decode_to_slice(value, &mut payload)?;
+        payload.truncate(len);
+        Ok(payload)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grpc_round_trip() {
+        assert!(is_binary_metadata_key(GRPC_METADATA_KEY));
+        assert!(!is_binary_metadata_key("branka-token"));
+        assert!(!is_binary_metadata_key("Branka-Token-bin"));
+
+        let branka = Branka::new(&[7u8; 32], 0);
+        let value = branka.encode_grpc(b"rpc").unwrap();
+        assert_eq!(value.len(), 3 + TOKEN_OVERHEAD);
+        assert_eq!(branka.decode_grpc(&value).unwrap(), b"rpc");
+        assert_eq!(
+            branka.decode_grpc(&value[..10]),
+            Err(BrankaError::InvalidDataLength)
+        );
+
+        let big = vec![0u8; MAX_GRPC_TOKEN_LEN + 1];
+        assert_eq!(branka.encode_grpc(&big), Err(BrankaError::TokenTooLong));
+        assert_eq!(branka.decode_grpc(&big), Err(BrankaError::TokenTooLong));
+    }
+}
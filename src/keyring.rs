@@ -0,0 +1,123 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Branka, BrankaError, Decoded};
+
+// A set of keys for rotation: tokens are minted with the primary key and
+// decoded with whichever key opens them, so tokens issued before a rotation
+// stay valid until the old key is retired.
+#[derive(Debug)]
+pub struct Keyring {
+    // Primary first, then older keys from newest to oldest.
+    keys: Vec<Branka>,
+}
+
+impl Keyring {
+    pub fn new(primary: Branka) -> Keyring {
+        Keyring {
+            keys: vec![primary],
+        }
+    }
+
+    // Accept tokens minted with an older key; add newest first.
+    pub fn with_previous(mut self, previous: Branka) -> Self {
+        self.keys.push(previous);
+        self
+    }
+
+    // Make `primary` the encoding key, keeping the current one for decoding.
+    pub fn rotate(&mut self, primary: Branka) {
+        self.keys.insert(0, primary);
+    }
+
+    // Drop the oldest decode-only key, returning it.
+    // The primary key is never retired.
+    pub fn retire_oldest(&mut self) -> Option<Branka> {
+        if self.keys.len() > 1 {
+            self.keys.pop()
+        } else {
+            None
+        }
+    }
+
+    pub fn primary(&self) -> &Branka {
+        &self.keys[0]
+    }
+
+    // Number of keys, primary included.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    // Always false: a keyring holds at least its primary key.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn encode(&self, data: &[u8]) -> String {
+        self.primary().encode(data)
+    }
+
+    pub fn try_encode(&self, data: &[u8]) -> Result<String, BrankaError> {
+        self.primary().try_encode(data)
+    }
+
+    // Decode with the first key that authenticates the token. Any other
+    // failure (expired, malformed, ...) is final, as it would be for every key.
+    pub fn decode(&self, data: &str) -> Result<Vec<u8>, BrankaError> {
+        self.try_keys(|key| key.decode(data))
+    }
+
+    pub fn encode_aad(&self, data: &[u8], aad: &[u8]) -> Result<String, BrankaError> {
+        self.primary().encode_aad(data, aad)
+    }
+
+    // Like decode, for tokens from encode_aad.
+    pub fn decode_aad(&self, data: &str, aad: &[u8]) -> Result<Vec<u8>, BrankaError> {
+        self.try_keys(|key| key.decode_aad(data, aad))
+    }
+
+    // Like decode, also returning the header fields.
+    pub(crate) fn decode_full(&self, data: &str) -> Result<Decoded, BrankaError> {
+        self.try_keys(|key| key.decode_full(data))
+    }
+
+    fn try_keys<T>(
+        &self,
+        decode: impl Fn(&Branka) -> Result<T, BrankaError>,
+    ) -> Result<T, BrankaError> {
+        let mut result = Err(BrankaError::InvalidData);
+        for key in &self.keys {
+            result = decode(key);
+            if !matches!(result, Err(BrankaError::InvalidData)) {
+                break;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyring_rotation() {
+        let mut keyring = Keyring::new(Branka::new(&[1u8; 32], 0));
+        let old = keyring.encode(b"old");
+
+        keyring.rotate(Branka::new(&[2u8; 32], 0));
+        let new = keyring.encode(b"new");
+        assert!(Branka::new(&[2u8; 32], 0).decode(&new).is_ok());
+        assert_eq!(keyring.decode(&old).unwrap(), b"old");
+        assert_eq!(keyring.decode(&new).unwrap(), b"new");
+
+        assert!(keyring.retire_oldest().is_some());
+        assert!(keyring.retire_oldest().is_none());
+        assert_eq!(keyring.decode(&old), Err(BrankaError::InvalidData));
+        assert_eq!(
+            keyring.decode("not base62!"),
+            Err(BrankaError::InvalidBase62)
+        );
+    }
+}
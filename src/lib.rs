@@ -1,102 +1,684 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[macro_use]
+extern crate alloc;
+
+mod aad;
+mod action;
+mod api_key;
+mod audit;
+mod base62;
+mod base64url;
+#[cfg(feature = "std")]
+mod batch;
+mod bearer;
+#[cfg(feature = "std")]
+mod cache;
+mod chunk;
+#[cfg(feature = "std")]
+mod claims;
+mod clock;
+mod compact;
+mod context;
+mod csrf;
+mod cursor;
+#[cfg(feature = "std")]
+mod denylist;
+mod detached;
+#[cfg(feature = "testing")]
+mod deterministic;
+#[cfg(feature = "std")]
+mod env;
+mod extensions;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod field;
+#[cfg(feature = "std")]
+mod file;
+mod grpc;
+mod keyring;
+mod magic_link;
+#[cfg(feature = "std")]
+mod map;
+mod message;
+mod migrate;
+mod multi;
+mod oauth;
+mod onetime;
+mod pair;
+#[cfg(feature = "rayon")]
+mod par;
+mod payload;
+#[cfg(feature = "std")]
+mod pool;
+mod registry;
+mod replay;
+mod report;
+mod revocation;
+mod scratch;
+mod sealed;
+mod sections;
+#[cfg(feature = "serde")]
+mod serde_payload;
+mod service;
+mod session;
+mod signed_url;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+// Internal codecs exposed to the fuzz targets and benchmarks. Not part of
+// the API.
+#[doc(hidden)]
+pub mod internals {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    pub fn base62_encode(input: &[u8]) -> String {
+        let mut out = String::new();
+        crate::base62::encode_into(&mut input.to_vec(), &mut out);
+        out
+    }
+
+    pub fn base62_decode(input: &str) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        crate::base62::decode_into(input, &mut out).ok()?;
+        Some(out)
+    }
+
+    pub fn base64url_decode(input: &str) -> Option<Vec<u8>> {
+        crate::base64url::decode(input).ok()
+    }
+}
+mod token;
+mod trace;
+#[cfg(feature = "serde_json")]
+mod value;
+mod verified;
+mod webhook;
+mod wrap;
+
+pub use action::{
+    ActionTokens, EMAIL_VERIFICATION, EMAIL_VERIFICATION_TTL, PASSWORD_RESET, PASSWORD_RESET_TTL,
+};
+pub use api_key::{ApiKey, ApiKeys};
+pub use audit::{AuditEvent, AuditSink};
+pub use bearer::{from_bearer, to_bearer};
+#[cfg(feature = "std")]
+pub use cache::CachedDecoder;
+pub use chunk::{chunk_token, reassemble_token, DEFAULT_CHUNK_SIZE};
+#[cfg(feature = "std")]
+pub use claims::{Claims, TokenBuilder, TokenVerifier, ValidationFailure};
+pub use clock::Clock;
+#[cfg(feature = "std")]
+pub use clock::SystemClock;
+pub use compact::{Compact, COMPACT_OVERHEAD};
+pub use context::{ContextBinder, RequestContext};
+pub use csrf::Csrf;
+pub use cursor::{Cursor, DEFAULT_CURSOR_LEN};
+#[cfg(feature = "std")]
+pub use denylist::FileDenyList;
+pub use detached::DetachedToken;
+#[cfg(feature = "testing")]
+pub use deterministic::DETERMINISTIC_TIMESTAMP;
+#[cfg(feature = "std")]
+pub use env::KeyError;
+pub use extensions::{
+    Extensions, EXT_AUDIENCE, EXT_CHUNK_INDEX, EXT_KEY_ID, EXT_PURPOSE, EXT_TRACE_CONTEXT,
+};
+pub use field::FieldCipher;
+pub use grpc::{is_binary_metadata_key, GRPC_METADATA_KEY, MAX_GRPC_TOKEN_LEN};
+pub use keyring::Keyring;
+pub use magic_link::{MagicLink, MagicLinkClaims, MAGIC_LINK, MAGIC_LINK_TTL};
+pub use message::{MessageOpener, MessageSealer, SealedMessage};
+pub use migrate::Migrator;
+pub use oauth::{OAuthState, OAuthStateClaims, OAUTH_STATE_TTL};
+#[cfg(feature = "std")]
+pub use onetime::MemoryUsedTokenStore;
+pub use onetime::{OneTimeToken, TokenId, UsedTokenStore};
+#[cfg(feature = "std")]
+pub use pair::MemoryRefreshStore;
+pub use pair::{Lineage, RefreshStore, TokenPair, TokenPairIssuer};
+#[cfg(feature = "rayon")]
+pub use par::ParallelBranka;
+pub use payload::Payload;
+#[cfg(feature = "std")]
+pub use pool::{
+    buffer_pool_size, clear_buffer_pool, set_buffer_pool_limit, DEFAULT_BUFFER_POOL_LIMIT,
+};
+pub use registry::Registry;
+pub use replay::ReplayGuard;
+pub use report::DecodeFailure;
+#[cfg(feature = "std")]
+pub use revocation::MemoryRevocationStore;
+pub use revocation::RevocationStore;
+pub use scratch::Scratch;
+pub use sealed::{open_config, open_value, seal_config, seal_value, SEALED_CONFIG_HEADER};
+pub use sections::Sections;
+#[cfg(feature = "serde")]
+pub use serde_payload::SerdePayload;
+pub use service::{scope_matches, Crud, ServiceToken};
+#[cfg(feature = "std")]
+pub use session::MemorySessionStore;
+pub use session::{Session, SessionId, SessionManager, SessionStore};
+pub use signed_url::{SignedUrl, UrlSigner};
+#[cfg(feature = "std")]
+pub use stream::{
+    BrankaReader, BrankaWriter, EncodeStream, StreamDecoder, DEFAULT_STREAM_CHUNK_SIZE,
+};
+pub use token::Token;
+pub use trace::TraceContext;
+pub use verified::VerifiedToken;
+pub use webhook::{WebhookSigner, WebhookVerifier, WEBHOOK_HEADER};
+
+use alloc::{borrow::Cow, string::String, sync::Arc, vec::Vec};
+use core::fmt;
+
 use byteorder::{BigEndian, ByteOrder};
 use chacha20poly1305::{
-    aead::{generic_array::GenericArray, AeadCore, AeadInPlace, KeyInit, OsRng},
+    aead::{generic_array::GenericArray, rand_core::RngCore, AeadInPlace, KeyInit, OsRng},
     Key, XChaCha20Poly1305, XNonce,
 };
+use zeroize::Zeroize;
 
 // Branka magic byte.
-const VERSION: u8 = 0xBA;
+pub(crate) const VERSION: u8 = 0xBA;
+// Version || Timestamp || Nonce, and the Poly1305 tag.
+pub const TOKEN_OVERHEAD: usize = 29 + 16;
+// Default cap on the length of tokens accepted by decode.
+pub const DEFAULT_MAX_TOKEN_LEN: usize = 8 * 1024;
 // Base 62 alphabet.
 const BASE62: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
 
+// Cloning is cheap: clones share the cipher state (and, for deterministic
+// instances, the nonce counter). Branka is Send + Sync, so one instance can
+// also be shared by reference across threads.
+#[derive(Clone)]
 pub struct Branka {
-    cipher: XChaCha20Poly1305,
+    cipher: Arc<XChaCha20Poly1305>,
     ttl: u32,
+    max_token_len: usize,
+    audit: Option<Arc<dyn AuditSink>>,
+    clock: Arc<dyn Clock>,
+    type_tags: bool,
+    #[cfg(feature = "std")]
+    pooled: bool,
+    #[cfg(feature = "testing")]
+    deterministic: Option<Arc<deterministic::Deterministic>>,
 }
 
-#[derive(Debug)]
+// Never print anything derived from the key.
+impl fmt::Debug for Branka {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Branka")
+            .field("key", &format_args!("[REDACTED]"))
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BrankaError {
     InvalidBase62,
     InvalidDataLength,
     InvalidVersion,
     InvalidData,
     Expired,
+    InvalidAuthorization,
+    InvalidChunks,
+    CsrfMismatch,
+    InvalidSession,
+    WrongTokenKind,
+    TokenReused,
+    Revoked,
+    TokenTooLong,
+    RngFailure,
+    BufferTooSmall,
+    InvalidStream,
+    InvalidPayload,
+    InvalidClaim,
+    InvalidBase64,
+    StoreFailure,
+    MissingChunk,
+}
+
+impl BrankaError {
+    // Stable machine-readable code, e.g. for API error responses. Codes never
+    // change once released; new variants get new codes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BrankaError::InvalidBase62 => "BRK-BAD-ENCODING",
+            BrankaError::InvalidDataLength => "BRK-BAD-LENGTH",
+            BrankaError::InvalidVersion => "BRK-BAD-VERSION",
+            BrankaError::InvalidData => "BRK-BAD-KEY",
+            BrankaError::Expired => "BRK-EXPIRED",
+            BrankaError::InvalidAuthorization => "BRK-BAD-AUTHORIZATION",
+            BrankaError::InvalidChunks => "BRK-BAD-CHUNKS",
+            BrankaError::CsrfMismatch => "BRK-CSRF-MISMATCH",
+            BrankaError::InvalidSession => "BRK-BAD-SESSION",
+            BrankaError::WrongTokenKind => "BRK-WRONG-KIND",
+            BrankaError::TokenReused => "BRK-REUSED",
+            BrankaError::Revoked => "BRK-REVOKED",
+            BrankaError::TokenTooLong => "BRK-TOO-LONG",
+            BrankaError::RngFailure => "BRK-RNG-FAILURE",
+            BrankaError::BufferTooSmall => "BRK-BUFFER-TOO-SMALL",
+            BrankaError::InvalidStream => "BRK-BAD-STREAM",
+            BrankaError::InvalidPayload => "BRK-BAD-PAYLOAD",
+            BrankaError::InvalidClaim => "BRK-BAD-CLAIM",
+            BrankaError::InvalidBase64 => "BRK-BAD-BASE64",
+            BrankaError::StoreFailure => "BRK-STORE-FAILURE",
+            BrankaError::MissingChunk => "BRK-MISSING-CHUNK",
+        }
+    }
 }
 
 impl Branka {
     // Create a new Branka instance.
     // key: 32 bytes key.
-    // ttl: Time to live in seconds, only used for decoding.
+    // ttl: Time to live in seconds, only used for decoding (0 disables expiry).
+    #[cfg(feature = "std")]
     pub fn new(key: &[u8], ttl: u32) -> Branka {
+        Branka::new_with_clock(key, ttl, Arc::new(SystemClock))
+    }
+
+    // Create a new Branka instance reading time from `clock`.
+    // This is the constructor to use without the `std` feature.
+    pub fn new_with_clock(key: &[u8], ttl: u32, clock: Arc<dyn Clock>) -> Branka {
         let key = Key::from_slice(key);
-        let cipher = XChaCha20Poly1305::new(&key);
-        Branka { cipher, ttl }
+        let cipher = Arc::new(XChaCha20Poly1305::new(key));
+        Branka {
+            cipher,
+            ttl,
+            max_token_len: DEFAULT_MAX_TOKEN_LEN,
+            audit: None,
+            clock,
+            type_tags: false,
+            #[cfg(feature = "std")]
+            pooled: false,
+            #[cfg(feature = "testing")]
+            deterministic: None,
+        }
+    }
+
+    // Create a Branka instance producing reproducible tokens, for golden-token
+    // snapshot tests and cross-language fixtures. TEST ONLY: nonces are
+    // predictable and the clock is frozen.
+    // seed: selects the nonce sequence.
+    #[cfg(feature = "testing")]
+    pub fn new_deterministic(key: &[u8], ttl: u32, seed: u64) -> Branka {
+        let clock = testing::MockClock::new(deterministic::DETERMINISTIC_TIMESTAMP);
+        let mut branka = Branka::new_with_clock(key, ttl, Arc::new(clock));
+        branka.deterministic = Some(Arc::new(deterministic::Deterministic::new(seed)));
+        branka
+    }
+
+    // Use `clock` as the time source for issuing and expiring tokens.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Branka {
+        self.clock = clock;
+        self
+    }
+
+    // Set the maximum token length (in characters) accepted by decode.
+    // Base62 decoding is quadratic in the input length, so longer inputs are
+    // rejected before any work is done on them.
+    pub fn with_max_token_len(mut self, max_token_len: usize) -> Branka {
+        self.max_token_len = max_token_len;
+        self
+    }
+
+    // Report every encode/decode outcome to `sink`.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Branka {
+        self.audit = Some(sink);
+        self
     }
 
+    // Panics if the OS random number generator fails; see try_encode.
     pub fn encode(&self, data: &[u8]) -> String {
-        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
-        let timestamp = get_timestamp();
+        self.try_encode(data)
+            .expect("failed to generate nonce from the OS random number generator")
+    }
+
+    // Like encode, but reports OS RNG failures (possible in some containers
+    // and jails) as BrankaError::RngFailure instead of panicking.
+    pub fn try_encode(&self, data: &[u8]) -> Result<String, BrankaError> {
+        #[cfg(feature = "std")]
+        if self.pooled {
+            let mut token = String::new();
+            pool::with_scratch(|scratch| self.encode_into(data, scratch, &mut token))?;
+            return Ok(token);
+        }
+
+        let nonce = self.next_nonce()?;
+        Ok(self.encode_with_nonce(&nonce, self.now(), data, &[]))
+    }
+
+    // Encode a raw binary token (no base62) into `out`, without allocating.
+    // Returns the token length, `data.len() + TOKEN_OVERHEAD`.
+    pub fn encode_to_slice(&self, data: &[u8], out: &mut [u8]) -> Result<usize, BrankaError> {
+        let len = data.len() + TOKEN_OVERHEAD;
+        if out.len() < len {
+            return Err(BrankaError::BufferTooSmall);
+        }
+        let nonce = self.next_nonce()?;
+        self.seal(&nonce, self.now(), data, &[], &mut out[..len]);
+        Ok(len)
+    }
+
+    // Like try_encode, but reuses the caller's buffers: `scratch` holds the
+    // binary token and `out` receives the base62 token, replacing their
+    // contents. Once both have grown to fit, encoding does not allocate.
+    pub fn encode_into(
+        &self,
+        data: &[u8],
+        scratch: &mut Vec<u8>,
+        out: &mut String,
+    ) -> Result<(), BrankaError> {
+        let nonce = self.next_nonce()?;
+        scratch.clear();
+        scratch.resize(data.len() + TOKEN_OVERHEAD, 0);
+        self.seal(&nonce, self.now(), data, &[], scratch);
+        base62::encode_into(scratch, out);
+        Ok(())
+    }
+
+    pub(crate) fn next_nonce(&self) -> Result<XNonce, BrankaError> {
+        #[cfg(feature = "testing")]
+        if let Some(deterministic) = &self.deterministic {
+            return Ok(deterministic.next_nonce());
+        }
+
+        let mut nonce = XNonce::default();
+        OsRng
+            .try_fill_bytes(&mut nonce)
+            .map_err(|_| BrankaError::RngFailure)?;
+        Ok(nonce)
+    }
 
+    pub(crate) fn encode_with_nonce(
+        &self,
+        nonce: &XNonce,
+        timestamp: u32,
+        data: &[u8],
+        aad: &[u8],
+    ) -> String {
+        let mut buf_crypt = vec![0u8; data.len() + TOKEN_OVERHEAD];
+        self.seal(nonce, timestamp, data, aad, &mut buf_crypt);
+        let mut token = String::new();
+        base62::encode_into(&mut buf_crypt, &mut token);
+        token
+    }
+
+    // Write the binary token for `data` into `out`, which must be exactly
+    // `data.len() + TOKEN_OVERHEAD` bytes long. `aad` is authenticated along
+    // with the header but not written.
+    fn seal(&self, nonce: &XNonce, timestamp: u32, data: &[u8], aad: &[u8], out: &mut [u8]) {
         // Version || Timestamp || Nonce
         let mut header = [0u8; 29];
         header[0] = VERSION;
         BigEndian::write_u32(&mut header[1..5], timestamp);
-        header[5..].copy_from_slice(&nonce);
+        header[5..].copy_from_slice(nonce);
 
-        let mut buf_crypt = vec![0u8; data.len() + 16 + 29];
-        buf_crypt[..29].copy_from_slice(&header);
-        buf_crypt[29..29 + data.len()].copy_from_slice(data);
+        out[..29].copy_from_slice(&header);
+        out[29..29 + data.len()].copy_from_slice(data);
 
         let sign = self
             .cipher
-            .encrypt_in_place_detached(&nonce, &header, &mut buf_crypt[29..29 + data.len()])
+            .encrypt_in_place_detached(
+                nonce,
+                &associated_data(&header, aad),
+                &mut out[29..29 + data.len()],
+            )
             .unwrap();
 
-        buf_crypt[29 + data.len()..].copy_from_slice(&sign);
-
-        base_x::encode(BASE62, &buf_crypt)
+        out[29 + data.len()..].copy_from_slice(&sign);
+        self.audit_encoded(timestamp, data.len());
     }
 
     pub fn decode(&self, data: &str) -> Result<Vec<u8>, BrankaError> {
-        let buf_crypt = base_x::decode(BASE62, data).map_err(|_| BrankaError::InvalidBase62)?;
-        if buf_crypt.len() < 29 + 16 {
-            return Err(BrankaError::InvalidDataLength);
+        self.decode_full(data).map(|decoded| decoded.payload)
+    }
+
+    // Like decode, but writes the payload into `out`, replacing its contents.
+    // `out` also holds the token while it is decrypted, so once it has grown
+    // to fit, decoding does not allocate. Cleared on failure.
+    pub fn decode_into(&self, data: &str, out: &mut Vec<u8>) -> Result<(), BrankaError> {
+        let result = self.decode_in_place(data, &[], out);
+        if result.is_err() {
+            out.clear();
         }
+        self.audit_decoded(
+            result
+                .as_ref()
+                .map(|(timestamp, _)| (*timestamp, out.len())),
+        );
+        result.map(|_| ())
+    }
 
-        let version = buf_crypt[0];
-        if version != VERSION {
-            return Err(BrankaError::InvalidVersion);
+    // Decode a raw binary token (as written by encode_to_slice) into `out`,
+    // without allocating. Returns the payload length.
+    pub fn decode_to_slice(&self, token: &[u8], out: &mut [u8]) -> Result<usize, BrankaError> {
+        let result = self.decode_to_slice_inner(token, &[], out);
+        self.audit_decoded(result.as_ref().map(|(timestamp, len)| (*timestamp, *len)));
+        result.map(|(_, len)| len)
+    }
+
+    fn decode_to_slice_inner(
+        &self,
+        token: &[u8],
+        aad: &[u8],
+        out: &mut [u8],
+    ) -> Result<(u32, usize), BrankaError> {
+        // Too short tokens are verified as an empty (zeroed) token instead.
+        let well_formed = token.len() >= TOKEN_OVERHEAD;
+        let empty = [0u8; TOKEN_OVERHEAD];
+        let token = if well_formed { token } else { &empty[..] };
+
+        let len = token.len() - TOKEN_OVERHEAD;
+        if out.len() < len {
+            return Err(BrankaError::BufferTooSmall);
         }
+        out[..len].copy_from_slice(&token[29..29 + len]);
 
-        let timestamp = BigEndian::read_u32(&buf_crypt[1..5]);
+        let timestamp = self.open(
+            &token[..29],
+            &mut out[..len],
+            &token[29 + len..],
+            aad,
+            well_formed,
+        )?;
+        Ok((timestamp, len))
+    }
 
-        let nonce = XNonce::from_slice(&buf_crypt[5..29]);
+    // Decode a token, also returning its header fields.
+    pub(crate) fn decode_full(&self, data: &str) -> Result<Decoded, BrankaError> {
+        self.decode_full_aad(data, &[])
+    }
 
-        let sign = GenericArray::from_slice(&buf_crypt[buf_crypt.len() - 16..]);
+    // Decode a token sealed with `aad`, also returning its header fields.
+    pub(crate) fn decode_full_aad(&self, data: &str, aad: &[u8]) -> Result<Decoded, BrankaError> {
+        let result = self.decode_inner(data, aad);
+        self.audit_decoded(
+            result
+                .as_ref()
+                .map(|decoded| (decoded.timestamp, decoded.payload.len())),
+        );
+        result
+    }
 
-        let mut buf = vec![0u8; buf_crypt.len() - 29 - 16];
-        buf.copy_from_slice(&buf_crypt[29..buf_crypt.len() - 16]);
+    fn audit_encoded(&self, timestamp: u32, payload_len: usize) {
+        if let Some(audit) = &self.audit {
+            audit.on_encoded(&AuditEvent {
+                timestamp,
+                payload_len,
+            });
+        }
+    }
 
-        self.cipher
-            .decrypt_in_place_detached(
-                &nonce,
-                &buf_crypt[..29],
-                &mut buf,
-                sign,
-            )
-            .map_err(|_| BrankaError::InvalidData)?;
+    fn audit_decoded(&self, result: Result<(u32, usize), &BrankaError>) {
+        if let Some(audit) = &self.audit {
+            match result {
+                Ok((timestamp, payload_len)) => audit.on_decoded(&AuditEvent {
+                    timestamp,
+                    payload_len,
+                }),
+                Err(err) => audit.on_rejected(err),
+            }
+        }
+    }
 
-        if timestamp > get_timestamp() + self.ttl {
+    fn decode_inner(&self, data: &str, aad: &[u8]) -> Result<Decoded, BrankaError> {
+        #[cfg(feature = "std")]
+        if self.pooled {
+            return pool::with_scratch(|buf| {
+                let (timestamp, nonce) = self.decode_in_place(data, aad, buf)?;
+                Ok(Decoded {
+                    timestamp,
+                    nonce,
+                    payload: buf.to_vec(),
+                })
+            });
+        }
+
+        let mut payload = Vec::new();
+        let (timestamp, nonce) = self.decode_in_place(data, aad, &mut payload)?;
+        Ok(Decoded {
+            timestamp,
+            nonce,
+            payload,
+        })
+    }
+
+    // Base62-decode `data` into `buf` and decrypt it there, leaving only the
+    // payload in `buf`. Returns the token timestamp and nonce.
+    fn decode_in_place(
+        &self,
+        data: &str,
+        aad: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> Result<(u32, TokenId), BrankaError> {
+        if data.len() > self.max_token_len {
+            return Err(BrankaError::TokenTooLong);
+        }
+        base62::decode_into(data, buf).map_err(|_| BrankaError::InvalidBase62)?;
+
+        // Too short tokens are verified as an empty (zeroed) token instead.
+        let well_formed = buf.len() >= TOKEN_OVERHEAD;
+        if !well_formed {
+            buf.clear();
+            buf.resize(TOKEN_OVERHEAD, 0);
+        }
+
+        let len = buf.len() - TOKEN_OVERHEAD;
+        let (header, rest) = buf.split_at_mut(29);
+        let (payload, sign) = rest.split_at_mut(len);
+        let opened = self.open(header, payload, sign, aad, well_formed);
+        let nonce = header[5..].try_into().unwrap();
+
+        // An expired token has been decrypted before being rejected.
+        let timestamp = match opened {
+            Ok(timestamp) => timestamp,
+            Err(err) => {
+                buf.zeroize();
+                return Err(err);
+            }
+        };
+
+        // Wipe the plaintext left past the payload once it is moved down,
+        // so it does not outlive the call in the buffer's spare capacity.
+        buf.copy_within(29..29 + len, 0);
+        buf[len..].zeroize();
+        buf.truncate(len);
+        Ok((timestamp, nonce))
+    }
+
+    // Verify and decrypt `buf` in place, returning the token timestamp.
+    // Malformed, wrong-key and expired tokens all go through the AEAD
+    // verification before any check is allowed to fail, so that they take
+    // comparable time and don't give auth endpoints a timing oracle.
+    fn open(
+        &self,
+        header: &[u8],
+        buf: &mut [u8],
+        sign: &[u8],
+        aad: &[u8],
+        well_formed: bool,
+    ) -> Result<u32, BrankaError> {
+        let version = header[0];
+
+        let timestamp = BigEndian::read_u32(&header[1..5]);
+
+        let nonce = XNonce::from_slice(&header[5..29]);
+
+        let sign = GenericArray::from_slice(sign);
+
+        let authentic = self
+            .cipher
+            .decrypt_in_place_detached(nonce, &associated_data(header, aad), buf, sign)
+            .is_ok();
+
+        if !well_formed {
+            return Err(BrankaError::InvalidDataLength);
+        }
+        if version != VERSION {
+            return Err(BrankaError::InvalidVersion);
+        }
+        if !authentic {
+            return Err(BrankaError::InvalidData);
+        }
+
+        if self.ttl != 0 && u64::from(timestamp) + u64::from(self.ttl) < u64::from(self.now()) {
             return Err(BrankaError::Expired);
         }
 
-        
-        Ok(buf)
+        Ok(timestamp)
+    }
+
+    // Current unix time as seen by this instance.
+    pub(crate) fn now(&self) -> u32 {
+        self.clock.now()
+    }
+
+    // Unix time after which tokens issued at `timestamp` are expired.
+    pub(crate) fn expires_at(&self, timestamp: u32) -> u32 {
+        if self.ttl == 0 {
+            u32::MAX
+        } else {
+            timestamp.saturating_add(self.ttl)
+        }
+    }
+
+    // The same key and clock with expiry disabled, for checks that report
+    // expiry themselves rather than failing on it.
+    #[cfg(feature = "std")]
+    pub(crate) fn without_ttl(&self) -> Branka {
+        let mut branka = self.clone();
+        branka.ttl = 0;
+        branka.audit = None;
+        branka
+    }
+}
+
+// AEAD associated data: the token header, followed by the caller's context
+// if any.
+fn associated_data<'a>(header: &'a [u8], aad: &[u8]) -> Cow<'a, [u8]> {
+    if aad.is_empty() {
+        Cow::Borrowed(header)
+    } else {
+        Cow::Owned([header, aad].concat())
     }
 }
 
+// A verified token along with its header fields.
+pub(crate) struct Decoded {
+    pub timestamp: u32,
+    pub nonce: TokenId,
+    pub payload: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
 #[inline]
-fn get_timestamp() -> u32 {
+pub(crate) fn get_timestamp() -> u32 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
@@ -105,10 +687,113 @@ fn get_timestamp() -> u32 {
     // 0
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_max_token_len() {
+        let branka = Branka::new(&[1u8; 32], 0).with_max_token_len(100);
+        let token = branka.encode(&[0u8; 16]);
+        assert!(branka.decode(&token).is_ok());
+
+        let token = branka.encode(&[0u8; 64]);
+        assert_eq!(branka.decode(&token), Err(BrankaError::TokenTooLong));
+    }
+
+    #[test]
+    fn test_slice_round_trip() {
+        let branka = Branka::new(&[1u8; 32], 0);
+        let mut token = [0u8; 64];
+        let len = branka.encode_to_slice(b"no alloc", &mut token).unwrap();
+        assert_eq!(len, 8 + TOKEN_OVERHEAD);
+
+        let mut out = [0u8; 8];
+        let n = branka.decode_to_slice(&token[..len], &mut out).unwrap();
+        assert_eq!(&out[..n], b"no alloc");
+
+        token[len - 1] ^= 1;
+        assert_eq!(
+            branka.decode_to_slice(&token[..len], &mut out),
+            Err(BrankaError::InvalidData)
+        );
+        assert_eq!(
+            branka.encode_to_slice(b"no alloc", &mut [0u8; 52]),
+            Err(BrankaError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_into_reuses_buffers() {
+        let branka = Branka::new(&[1u8; 32], 0);
+        let (mut scratch, mut token, mut payload) = (Vec::new(), String::new(), Vec::new());
+        for data in [&b"first payload"[..], b"second"] {
+            branka.encode_into(data, &mut scratch, &mut token).unwrap();
+            branka.decode_into(&token, &mut payload).unwrap();
+            assert_eq!(payload, data);
+        }
+
+        let other = Branka::new(&[2u8; 32], 0).encode(b"secret");
+        assert_eq!(
+            branka.decode_into(&other, &mut payload),
+            Err(BrankaError::InvalidData)
+        );
+        assert!(payload.is_empty());
+    }
+
+    #[test]
+    fn test_decode_wipes_spare_capacity() {
+        struct Epoch;
+        impl Clock for Epoch {
+            fn now(&self) -> u32 {
+                1
+            }
+        }
+
+        let payload = [0x5Au8; 40];
+        let token = Branka::new_with_clock(&[1u8; 32], 0, Arc::new(Epoch)).encode(&payload);
+        let expired = Branka::new(&[1u8; 32], 1);
+        for (branka, len) in [(Branka::new(&[1u8; 32], 0), payload.len()), (expired, 0)] {
+            let mut out = Vec::new();
+            let _ = branka.decode_into(&token, &mut out);
+            assert_eq!(out.len(), len);
+
+            // The whole decoded token was written to `out`, so the bytes past
+            // its length are initialized.
+            let written = payload.len() + TOKEN_OVERHEAD;
+            assert!(out.capacity() >= written);
+            unsafe { out.set_len(written) };
+            assert!(out[len..].iter().all(|&byte| byte == 0));
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_key() {
+        let branka = Branka::new(&[0xAB; 32], 3000);
+        assert_eq!(
+            format!("{:?}", branka),
+            "Branka { key: [REDACTED], ttl: 3000 }"
+        );
+    }
+
+    #[test]
+    fn test_clone_shares_key() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Branka>();
+
+        let branka = Branka::new(&[0xCD; 32], 0);
+        let clone = branka.clone();
+        assert!(Arc::ptr_eq(&branka.cipher, &clone.cipher));
+        assert_eq!(clone.decode(&branka.encode(b"shared")).unwrap(), b"shared");
+    }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        assert_eq!(BrankaError::Expired.code(), "BRK-EXPIRED");
+        assert_eq!(BrankaError::InvalidData.code(), "BRK-BAD-KEY");
+        assert_eq!(BrankaError::TokenReused.code(), "BRK-REUSED");
+    }
+
     #[test]
     fn test_encode_decode_with_other_impls() {
         let mut key = [0u8; 32];
@@ -121,7 +806,6 @@ mod tests {
         let mut branca2 = branca::Branca::new(&key).unwrap();
         let token2 = branca2.encode(data.as_bytes()).unwrap();
 
-
         // check if both tokens are valid and can be decoded by both implementations
         let d_1_1 = branca1.decode(&token1).unwrap();
         assert_eq!(d_1_1, data.as_bytes());
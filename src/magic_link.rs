@@ -0,0 +1,103 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::action::ActionTokens;
+use crate::{Branka, BrankaError, UsedTokenStore};
+
+pub const MAGIC_LINK: &str = "magic-link";
+
+// Time to live of the tokens made by MagicLink::with_key.
+pub const MAGIC_LINK_TTL: u32 = 10 * 60;
+
+// What a verified magic link logs in: the address it was mailed to and
+// where to send the user afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagicLinkClaims {
+    pub email: String,
+    pub redirect: String,
+}
+
+// Passwordless login links. Each token is bound to an email address and a
+// redirect target and works once.
+pub struct MagicLink<S> {
+    tokens: ActionTokens<S>,
+}
+
+impl<S: UsedTokenStore> MagicLink<S> {
+    // The TTL of `branka` bounds how long links stay valid: keep it short.
+    pub fn new(branka: Branka, store: S) -> Self {
+        MagicLink {
+            tokens: ActionTokens::new(branka, store, MAGIC_LINK),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_key(key: &[u8], store: S) -> Self {
+        MagicLink::new(Branka::new(key, MAGIC_LINK_TTL), store)
+    }
+
+    // Mint a token for `email`. `redirect` is returned as-is on login: pass
+    // a path on your own site, never one taken unchecked from the request.
+    // Panics if `email` is longer than 65535 bytes.
+    pub fn issue(&self, email: &str, redirect: &str) -> String {
+        assert!(email.len() <= u16::MAX as usize, "email too long");
+        // Email length || Email || Redirect
+        let mut payload = Vec::with_capacity(2 + email.len() + redirect.len());
+        payload.extend_from_slice(&(email.len() as u16).to_be_bytes());
+        payload.extend_from_slice(email.as_bytes());
+        payload.extend_from_slice(redirect.as_bytes());
+        self.tokens.issue_bytes(&payload)
+    }
+
+    // The link to mail: `base_url` with the token as its `token` query
+    // parameter. Base62 tokens need no escaping.
+    pub fn url(&self, base_url: &str, email: &str, redirect: &str) -> String {
+        let separator = if base_url.contains('?') { '&' } else { '?' };
+        let mut url = String::from(base_url);
+        url.push(separator);
+        url.push_str("token=");
+        url.push_str(&self.issue(email, redirect));
+        url
+    }
+
+    // Verify the token and consume it; a second use fails with TokenReused.
+    pub fn verify(&self, token: &str) -> Result<MagicLinkClaims, BrankaError> {
+        let payload = self.tokens.verify_bytes(token)?;
+        if payload.len() < 2 {
+            return Err(BrankaError::InvalidPayload);
+        }
+        let email_len = usize::from(BigEndian::read_u16(&payload));
+        let (email, redirect) = payload[2..]
+            .split_at_checked(email_len)
+            .ok_or(BrankaError::InvalidPayload)?;
+        let text = |bytes: &[u8]| {
+            String::from_utf8(bytes.to_vec()).map_err(|_| BrankaError::InvalidPayload)
+        };
+        Ok(MagicLinkClaims {
+            email: text(email)?,
+            redirect: text(redirect)?,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::MemoryUsedTokenStore;
+
+    #[test]
+    fn test_magic_link() {
+        let links = MagicLink::with_key(&[1u8; 32], MemoryUsedTokenStore::new());
+        let url = links.url("https://example.com/login", "ada@example.com", "/inbox");
+        let token = url
+            .strip_prefix("https://example.com/login?token=")
+            .unwrap();
+
+        let claims = links.verify(token).unwrap();
+        assert_eq!(claims.email, "ada@example.com");
+        assert_eq!(claims.redirect, "/inbox");
+        assert_eq!(links.verify(token), Err(BrankaError::TokenReused));
+    }
+}
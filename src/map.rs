@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{Branka, BrankaError, Payload};
+
+// Payload: Count || (Key length || Key || Value length || Value)*, counts and
+// lengths as u32 BE, entries sorted by key so equal maps give equal payloads.
+impl Payload for HashMap<String, String> {
+    fn to_payload(&self) -> Vec<u8> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable();
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        for (key, value) in entries {
+            for text in [key, value] {
+                payload.extend_from_slice(&(text.len() as u32).to_be_bytes());
+                payload.extend_from_slice(text.as_bytes());
+            }
+        }
+        payload
+    }
+
+    fn from_payload(mut bytes: &[u8]) -> Result<Self, BrankaError> {
+        let count = read_u32(&mut bytes)?;
+        // Every entry takes at least 8 bytes: don't trust `count` further.
+        let mut map = HashMap::with_capacity(count.min(bytes.len() / 8));
+        for _ in 0..count {
+            let key = read_string(&mut bytes)?;
+            let value = read_string(&mut bytes)?;
+            if map.insert(key, value).is_some() {
+                return Err(BrankaError::InvalidPayload);
+            }
+        }
+        if !bytes.is_empty() {
+            return Err(BrankaError::InvalidPayload);
+        }
+        Ok(map)
+    }
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<usize, BrankaError> {
+    let (len, rest) = bytes
+        .split_first_chunk::<4>()
+        .ok_or(BrankaError::InvalidPayload)?;
+    *bytes = rest;
+    Ok(BigEndian::read_u32(len) as usize)
+}
+
+fn read_string(bytes: &mut &[u8]) -> Result<String, BrankaError> {
+    let len = read_u32(bytes)?;
+    let (text, rest) = bytes
+        .split_at_checked(len)
+        .ok_or(BrankaError::InvalidPayload)?;
+    *bytes = rest;
+    String::from_utf8(text.to_vec()).map_err(|_| BrankaError::InvalidPayload)
+}
+
+impl Branka {
+    // Encode a few string claims without defining a payload type.
+    pub fn encode_map(&self, claims: &HashMap<String, String>) -> Result<String, BrankaError> {
+        self.try_encode(&claims.to_payload())
+    }
+
+    pub fn decode_map(&self, data: &str) -> Result<HashMap<String, String>, BrankaError> {
+        HashMap::from_payload(&self.decode(data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_round_trip() {
+        let branka = Branka::new(&[3u8; 32], 0);
+        let claims = HashMap::from([
+            ("sub".to_string(), "user:1".to_string()),
+            ("role".to_string(), "admin".to_string()),
+        ]);
+        let token = branka.encode_map(&claims).unwrap();
+        assert_eq!(branka.decode_map(&token).unwrap(), claims);
+
+        let empty = branka.encode_map(&HashMap::new()).unwrap();
+        assert!(branka.decode_map(&empty).unwrap().is_empty());
+
+        let garbage = branka.encode(b"\x00\x00\x00\x01\x00");
+        assert_eq!(
+            branka.decode_map(&garbage),
+            Err(BrankaError::InvalidPayload)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::extensions::Extensions;
+use crate::{Branka, BrankaError, Payload};
+
+// A sealed queue message (Kafka, NATS, SQS, ...) carrying a `T`: a v2 token
+// whose header names the key it was sealed with.
+pub struct SealedMessage<T> {
+    token: String,
+    payload: PhantomData<fn() -> T>,
+}
+
+impl<T> SealedMessage<T> {
+    // Treat a received message body as carrying a `T`; MessageOpener::open
+    // still verifies it.
+    pub fn new(token: String) -> SealedMessage<T> {
+        SealedMessage {
+            token,
+            payload: PhantomData,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.token.as_bytes()
+    }
+
+    pub fn into_string(self) -> String {
+        self.token
+    }
+}
+
+impl<T> fmt::Debug for SealedMessage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SealedMessage").field(&self.token).finish()
+    }
+}
+
+// Producer side: seals messages with one key, tagged with its id.
+pub struct MessageSealer {
+    key_id: Vec<u8>,
+    branka: Branka,
+}
+
+impl MessageSealer {
+    // key_id: up to 255 bytes, shared with consumers to select the key.
+    pub fn new(key_id: &[u8], branka: Branka) -> MessageSealer {
+        MessageSealer {
+            key_id: key_id.to_vec(),
+            branka,
+        }
+    }
+
+    pub fn seal<T: Payload>(&self, value: &T) -> Result<SealedMessage<T>, BrankaError> {
+        let extensions = Extensions::new().with_key_id(&self.key_id);
+        let token = self.branka.encode_v2(&value.to_payload(), &extensions)?;
+        Ok(SealedMessage::new(token))
+    }
+}
+
+// Consumer side: verifies messages with the key named in their header and
+// rejects messages older than `max_age` seconds (0 for no limit).
+pub struct MessageOpener {
+    keys: Vec<(Vec<u8>, Branka)>,
+    max_age: u32,
+}
+
+impl MessageOpener {
+    pub fn new(max_age: u32) -> MessageOpener {
+        MessageOpener {
+            keys: Vec::new(),
+            max_age,
+        }
+    }
+
+    // Accept messages sealed by the producer key `key_id`.
+    pub fn with_key(mut self, key_id: &[u8], branka: Branka) -> Self {
+        self.keys.push((key_id.to_vec(), branka));
+        self
+    }
+
+    // Verify and decode a message. Messages under an unknown key id fail
+    // with InvalidData, stale ones with Expired.
+    pub fn open<T: Payload>(&self, message: &SealedMessage<T>) -> Result<T, BrankaError> {
+        let branka = self.key_for(message.as_str())?;
+        let decoded = branka.decode_v2_full(message.as_str())?;
+        if self.max_age != 0 && branka.now().saturating_sub(decoded.timestamp) > self.max_age {
+            return Err(BrankaError::Expired);
+        }
+        T::from_payload(&decoded.payload)
+    }
+
+    // The key named in the (not yet verified) header. Decoding with it then
+    // authenticates the header, key id included.
+    fn key_for(&self, token: &str) -> Result<&Branka, BrankaError> {
+        let (_, any) = self.keys.first().ok_or(BrankaError::InvalidData)?;
+        let extensions = any.peek_extensions(token)?;
+        let key_id = extensions.key_id().ok_or(BrankaError::InvalidData)?;
+        self.keys
+            .iter()
+            .find(|(id, _)| id == key_id)
+            .map(|(_, branka)| branka)
+            .ok_or(BrankaError::InvalidData)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Clock;
+    use alloc::sync::Arc;
+
+    struct At(u32);
+
+    impl Clock for At {
+        fn now(&self) -> u32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_message_seal_open() {
+        let key = [4u8; 32];
+        let sealer = MessageSealer::new(b"k2", Branka::new_with_clock(&key, 0, Arc::new(At(1000))));
+        let message = sealer.seal(&String::from("order:9")).unwrap();
+
+        let opener = MessageOpener::new(60)
+            .with_key(b"k1", Branka::new(&[3u8; 32], 0))
+            .with_key(b"k2", Branka::new_with_clock(&key, 0, Arc::new(At(1060))));
+        assert_eq!(opener.open(&message).unwrap(), "order:9");
+
+        let stale = MessageOpener::new(60)
+            .with_key(b"k2", Branka::new_with_clock(&key, 0, Arc::new(At(1061))));
+        assert_eq!(stale.open(&message), Err(BrankaError::Expired));
+
+        let unknown = MessageOpener::new(60).with_key(b"k1", Branka::new(&key, 0));
+        assert_eq!(unknown.open(&message), Err(BrankaError::InvalidData));
+    }
+}
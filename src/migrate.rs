@@ -0,0 +1,80 @@
+use alloc::string::String;
+
+use crate::{Branka, BrankaError, Keyring};
+
+// Re-encrypts existing tokens under a new key, e.g. to rewrite a database
+// column of stored tokens before retiring the key they were minted with.
+// Migrated tokens keep their original timestamp, so they expire when the
+// originals would have.
+pub struct Migrator {
+    from: Keyring,
+    to: Branka,
+}
+
+impl Migrator {
+    // from: the keys the existing tokens may be sealed with.
+    // to: the key new tokens are sealed with.
+    pub fn new(from: Keyring, to: Branka) -> Migrator {
+        Migrator { from, to }
+    }
+
+    pub fn migrate(&self, token: &str) -> Result<String, BrankaError> {
+        let decoded = self.from.decode_full(token)?;
+        let nonce = self.to.next_nonce()?;
+        Ok(self
+            .to
+            .encode_with_nonce(&nonce, decoded.timestamp, &decoded.payload, &[]))
+    }
+
+    // Migrate every token, spreading the work over the available cores.
+    // One result per token, in the order of `tokens`.
+    #[cfg(feature = "std")]
+    pub fn migrate_batch<T: AsRef<str> + Sync>(
+        &self,
+        tokens: &[T],
+    ) -> alloc::vec::Vec<Result<String, BrankaError>> {
+        self.to.batch(tokens, |token| self.migrate(token.as_ref()))
+    }
+
+    // Migrate tokens lazily as they are pulled from `tokens`, for sources
+    // too large to hold in memory (a database cursor, a file of lines).
+    pub fn migrate_iter<'a, I>(
+        &'a self,
+        tokens: I,
+    ) -> impl Iterator<Item = Result<String, BrankaError>> + 'a
+    where
+        I: IntoIterator + 'a,
+        I::Item: AsRef<str>,
+    {
+        tokens
+            .into_iter()
+            .map(move |token| self.migrate(token.as_ref()))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_keeps_payload_and_timestamp() {
+        let old = Branka::new(&[1u8; 32], 0);
+        let token = old.encode(b"stored");
+        let migrator = Migrator::new(Keyring::new(old), Branka::new(&[2u8; 32], 0));
+
+        let migrated = migrator.migrate(&token).unwrap();
+        let new = Branka::new(&[2u8; 32], 0);
+        assert_eq!(new.decode(&migrated).unwrap(), b"stored");
+        assert_eq!(
+            new.decode_full(&migrated).unwrap().timestamp,
+            Branka::new(&[1u8; 32], 0)
+                .decode_full(&token)
+                .unwrap()
+                .timestamp
+        );
+
+        let results: alloc::vec::Vec<_> = migrator.migrate_iter([token.as_str(), "bad"]).collect();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use zeroize::Zeroize;
+
+use crate::{base62, Branka, BrankaError, TOKEN_OVERHEAD};
+
+// A content key sealed for one recipient.
+const WRAPPED_KEY: usize = 32 + TOKEN_OVERHEAD;
+
+// Associated data of wrapped content keys: keeps a wrapped key from being
+// accepted as any other token minted under the recipient's key, and the
+// reverse.
+const CONTENT_KEY_AAD: &[u8] = b"branka:multi-recipient-key:v1";
+
+// Multi-recipient tokens encrypt the payload once under a random content
+// key, and seal that key for every recipient:
+//
+// Recipient count || Wrapped content key * count || Content token
+//
+// where the wrapped keys and the content token are binary branka tokens.
+impl Branka {
+    // Encrypt `data` so that any of `recipients` (1 to 255) can decode it
+    // with decode_multi. Other recipient counts fail with InvalidPayload.
+    pub fn encode_multi(recipients: &[&Branka], data: &[u8]) -> Result<String, BrankaError> {
+        if !(1..=255).contains(&recipients.len()) {
+            return Err(BrankaError::InvalidPayload);
+        }
+        let mut content_key = [0u8; 32];
+        OsRng
+            .try_fill_bytes(&mut content_key)
+            .map_err(|_| BrankaError::RngFailure)?;
+        let timestamp = recipients[0].now();
+
+        let wrapped_len = recipients.len() * WRAPPED_KEY;
+        let mut buf = vec![0u8; 1 + wrapped_len + data.len() + TOKEN_OVERHEAD];
+        buf[0] = recipients.len() as u8;
+        for (recipient, out) in recipients
+            .iter()
+            .zip(buf[1..1 + wrapped_len].chunks_mut(WRAPPED_KEY))
+        {
+            recipient.seal(
+                &recipient.next_nonce()?,
+                timestamp,
+                &content_key,
+                CONTENT_KEY_AAD,
+                out,
+            );
+        }
+
+        let content = Branka::new_with_clock(&content_key, 0, recipients[0].clock.clone());
+        let nonce = content.next_nonce()?;
+        content.seal(&nonce, timestamp, data, &[], &mut buf[1 + wrapped_len..]);
+        content_key.zeroize();
+
+        let mut token = String::new();
+        base62::encode_into(&mut buf, &mut token);
+        Ok(token)
+    }
+
+    // Decode a token from encode_multi addressed to this key.
+    pub fn decode_multi(&self, data: &str) -> Result<Vec<u8>, BrankaError> {
+        let result = self.decode_multi_inner(data);
+        self.audit_decoded(
+            result
+                .as_ref()
+                .map(|(timestamp, payload)| (*timestamp, payload.len())),
+        );
+        result.map(|(_, payload)| payload)
+    }
+
+    fn decode_multi_inner(&self, data: &str) -> Result<(u32, Vec<u8>), BrankaError> {
+        if data.len() > self.max_token_len {
+            return Err(BrankaError::TokenTooLong);
+        }
+        let mut buf = Vec::new();
+        base62::decode_into(data, &mut buf).map_err(|_| BrankaError::InvalidBase62)?;
+
+        let wrapped_len = usize::from(*buf.first().unwrap_or(&0)) * WRAPPED_KEY;
+        if wrapped_len == 0 || buf.len() < 1 + wrapped_len + TOKEN_OVERHEAD {
+            return Err(BrankaError::InvalidDataLength);
+        }
+        let (wrapped, content) = buf[1..].split_at(wrapped_len);
+
+        let mut content_key = [0u8; 32];
+        let mut result = Err(BrankaError::InvalidData);
+        for wrapped in wrapped.chunks(WRAPPED_KEY) {
+            result = self.decode_to_slice_inner(wrapped, CONTENT_KEY_AAD, &mut content_key);
+            if result != Err(BrankaError::InvalidData) {
+                break;
+            }
+        }
+        result?;
+
+        let content_branka = Branka::new_with_clock(&content_key, self.ttl, self.clock.clone());
+        content_key.zeroize();
+        let mut payload = vec![0u8; content.len() - TOKEN_OVERHEAD];
+        let (timestamp, _) = content_branka.decode_to_slice_inner(content, &[], &mut payload)?;
+        Ok((timestamp, payload))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_recipient() {
+        let nodes: Vec<Branka> = (1..=3u8).map(|i| Branka::new(&[i; 32], 0)).collect();
+        let recipients: Vec<&Branka> = nodes.iter().collect();
+        let token = Branka::encode_multi(&recipients, b"bootstrap").unwrap();
+
+        for node in &nodes {
+            assert_eq!(node.decode_multi(&token).unwrap(), b"bootstrap");
+        }
+        assert_eq!(
+            Branka::new(&[9u8; 32], 0).decode_multi(&token),
+            Err(BrankaError::InvalidData)
+        );
+        assert_eq!(
+            nodes[0].decode_multi("0"),
+            Err(BrankaError::InvalidDataLength)
+        );
+        assert_eq!(
+            Branka::encode_multi(&[], b"bootstrap"),
+            Err(BrankaError::InvalidPayload)
+        );
+
+        // A wrapped content key is not a token of its recipient.
+        let mut buf = Vec::new();
+        base62::decode_into(&token, &mut buf).unwrap();
+        let mut wrapped = String::new();
+        base62::encode_into(&mut buf[1..1 + WRAPPED_KEY], &mut wrapped);
+        assert_eq!(nodes[0].decode(&wrapped), Err(BrankaError::InvalidData));
+    }
+}
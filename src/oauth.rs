@@ -0,0 +1,106 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use byteorder::{BigEndian, ByteOrder};
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+
+use crate::{Branka, BrankaError};
+
+// Time to live of the states made by OAuthState::with_key: the time a user
+// has to get through the authorization server.
+pub const OAUTH_STATE_TTL: u32 = 10 * 60;
+
+// What the client needs back on the OAuth2 callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthStateClaims {
+    // Random value also usable as the OpenID Connect `nonce`.
+    pub nonce: [u8; 16],
+    pub redirect: String,
+    pub pkce_verifier: String,
+}
+
+// Stateless OAuth2 `state` parameters. The state is a token carrying the
+// nonce, the post-login redirect and the PKCE code verifier, so the callback
+// needs no server-side state store. The verifier itself is sealed (not a
+// hash of it): the client needs it for the token request. Each state is
+// bound to the user's session (a cookie value) as associated data, so a
+// state started in one browser is rejected in another.
+pub struct OAuthState {
+    branka: Branka,
+}
+
+impl OAuthState {
+    // The TTL of `branka` bounds how long states stay valid: keep it short.
+    pub fn new(branka: Branka) -> OAuthState {
+        OAuthState { branka }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_key(key: &[u8]) -> OAuthState {
+        OAuthState::new(Branka::new(key, OAUTH_STATE_TTL))
+    }
+
+    // Mint the `state` for an authorization request, returning it along
+    // with its nonce. Panics if `redirect` is longer than 65535 bytes.
+    pub fn issue(
+        &self,
+        session: &[u8],
+        redirect: &str,
+        pkce_verifier: &str,
+    ) -> Result<(String, [u8; 16]), BrankaError> {
+        assert!(redirect.len() <= u16::MAX as usize, "redirect too long");
+        let mut nonce = [0u8; 16];
+        OsRng
+            .try_fill_bytes(&mut nonce)
+            .map_err(|_| BrankaError::RngFailure)?;
+
+        // Nonce || Redirect length || Redirect || PKCE verifier
+        let mut payload = Vec::with_capacity(16 + 2 + redirect.len() + pkce_verifier.len());
+        payload.extend_from_slice(&nonce);
+        payload.extend_from_slice(&(redirect.len() as u16).to_be_bytes());
+        payload.extend_from_slice(redirect.as_bytes());
+        payload.extend_from_slice(pkce_verifier.as_bytes());
+        let state = self.branka.encode_aad(&payload, session)?;
+        Ok((state, nonce))
+    }
+
+    // Validate the `state` received on the callback for `session`.
+    pub fn verify(&self, session: &[u8], state: &str) -> Result<OAuthStateClaims, BrankaError> {
+        let payload = self.branka.decode_aad(state, session)?;
+        if payload.len() < 16 + 2 {
+            return Err(BrankaError::InvalidPayload);
+        }
+        let redirect_len = usize::from(BigEndian::read_u16(&payload[16..18]));
+        let (redirect, pkce_verifier) = payload[18..]
+            .split_at_checked(redirect_len)
+            .ok_or(BrankaError::InvalidPayload)?;
+        let text = |bytes: &[u8]| {
+            String::from_utf8(bytes.to_vec()).map_err(|_| BrankaError::InvalidPayload)
+        };
+        Ok(OAuthStateClaims {
+            nonce: payload[..16].try_into().unwrap(),
+            redirect: text(redirect)?,
+            pkce_verifier: text(pkce_verifier)?,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oauth_state() {
+        let states = OAuthState::with_key(&[3u8; 32]);
+        let (state, nonce) = states.issue(b"sid:1", "/settings", "verifier-123").unwrap();
+
+        let claims = states.verify(b"sid:1", &state).unwrap();
+        assert_eq!(claims.nonce, nonce);
+        assert_eq!(claims.redirect, "/settings");
+        assert_eq!(claims.pkce_verifier, "verifier-123");
+        assert_eq!(
+            states.verify(b"sid:2", &state),
+            Err(BrankaError::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use crate::{Branka, BrankaError};
+
+// Unique token identifier: the token's random nonce.
+pub type TokenId = [u8; 24];
+
+// Records which tokens have already been consumed.
+pub trait UsedTokenStore {
+    // Mark a token as used until `expires_at` (unix time), after which the
+    // entry may be forgotten since the token no longer decodes anyway.
+    // `now` is the current time according to the decoding Branka's clock.
+    // Returns false if the token was already used.
+    fn mark_used(&self, id: &TokenId, expires_at: u32, now: u32) -> bool;
+}
+
+// In-process store; expired entries are pruned as new ones are added.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct MemoryUsedTokenStore {
+    used: Mutex<HashMap<TokenId, u32>>,
+}
+
+#[cfg(feature = "std")]
+impl MemoryUsedTokenStore {
+    pub fn new() -> MemoryUsedTokenStore {
+        MemoryUsedTokenStore::default()
+    }
+}
+
+#[cfg(feature = "std")]
+impl UsedTokenStore for MemoryUsedTokenStore {
+    fn mark_used(&self, id: &TokenId, expires_at: u32, now: u32) -> bool {
+        let mut used = self.used.lock().unwrap();
+        used.retain(|_, expires_at| *expires_at >= now);
+        used.insert(*id, expires_at).is_none()
+    }
+}
+
+// Tokens that can be decoded only once, e.g. for email confirmation links
+// and payment callbacks.
+pub struct OneTimeToken<S> {
+    branka: Branka,
+    store: S,
+}
+
+impl<S: UsedTokenStore> OneTimeToken<S> {
+    pub fn new(branka: Branka, store: S) -> Self {
+        OneTimeToken { branka, store }
+    }
+
+    pub fn encode(&self, data: &[u8]) -> String {
+        self.branka.encode(data)
+    }
+
+    // Decode the token and consume it; later calls fail with TokenReused.
+    pub fn decode(&self, token: &str) -> Result<Vec<u8>, BrankaError> {
+        let decoded = self.branka.decode_full(token)?;
+        let expires_at = self.branka.expires_at(decoded.timestamp);
+        if !self
+            .store
+            .mark_used(&decoded.nonce, expires_at, self.branka.now())
+        {
+            return Err(BrankaError::TokenReused);
+        }
+        Ok(decoded.payload)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_time_token_rejects_reuse() {
+        let tokens = OneTimeToken::new(Branka::new(&[9u8; 32], 3600), MemoryUsedTokenStore::new());
+        let token = tokens.encode(b"confirm:alice@example.com");
+
+        assert_eq!(tokens.decode(&token).unwrap(), b"confirm:alice@example.com");
+        assert!(matches!(
+            tokens.decode(&token),
+            Err(BrankaError::TokenReused)
+        ));
+
+        let other = tokens.encode(b"confirm:alice@example.com");
+        assert!(tokens.decode(&other).is_ok());
+    }
+}
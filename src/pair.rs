@@ -0,0 +1,259 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use byteorder::{BigEndian, ByteOrder};
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+
+use crate::{Branka, BrankaError};
+
+const ACCESS: u8 = 0;
+const REFRESH: u8 = 1;
+
+// Kind || Lineage || Generation
+const PAIR_HEADER: usize = 1 + 16 + 4;
+
+pub type Lineage = [u8; 16];
+
+// Tracks how far each lineage has been refreshed, so that every refresh
+// token is redeemed at most once.
+pub trait RefreshStore {
+    // Redeem the refresh token of `generation` in `lineage` (the first pair
+    // of a lineage is generation 0). Returns false if that token was already
+    // redeemed, or the lineage was revoked; a replayed refresh token means it
+    // leaked, so the store must then also revoke the whole lineage.
+    // The lineage may be forgotten after `expires_at` (unix time), when the
+    // refresh token minted in exchange expires. `now` is the current time
+    // according to the issuer's clock.
+    fn redeem(&self, lineage: &Lineage, generation: u32, expires_at: u32, now: u32) -> bool;
+}
+
+// Number of lineages a MemoryRefreshStore holds before its first prune.
+#[cfg(feature = "std")]
+const MIN_PRUNE_AT: usize = 64;
+
+// In-process store. Expired lineages are pruned on redeem whenever the
+// store has doubled in size since the last prune.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct MemoryRefreshStore {
+    lineages: Mutex<Lineages>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Lineages {
+    // Next generation to redeem (None once revoked), expires at.
+    live: HashMap<Lineage, (Option<u32>, u32)>,
+    prune_at: usize,
+}
+
+#[cfg(feature = "std")]
+impl MemoryRefreshStore {
+    pub fn new() -> MemoryRefreshStore {
+        MemoryRefreshStore::default()
+    }
+
+    // Number of lineages held, expired ones included until pruned.
+    pub fn len(&self) -> usize {
+        self.lineages.lock().unwrap().live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "std")]
+impl RefreshStore for MemoryRefreshStore {
+    fn redeem(&self, lineage: &Lineage, generation: u32, expires_at: u32, now: u32) -> bool {
+        let mut lineages = self.lineages.lock().unwrap();
+        if lineages.live.len() >= lineages.prune_at {
+            lineages
+                .live
+                .retain(|_, &mut (_, expires_at)| expires_at >= now);
+            lineages.prune_at = (lineages.live.len() * 2).max(MIN_PRUNE_AT);
+        }
+        let entry = lineages
+            .live
+            .entry(*lineage)
+            .or_insert((Some(0), expires_at));
+        if entry.0 != Some(generation) {
+            entry.0 = None;
+            return false;
+        }
+        *entry = (generation.checked_add(1), expires_at);
+        true
+    }
+}
+
+// A short-lived access token and a long-lived refresh token sharing a lineage id.
+pub struct TokenPair {
+    pub access: String,
+    pub refresh: String,
+    pub lineage: Lineage,
+}
+
+// Mints and rotates access/refresh token pairs.
+// Both tokens carry the same payload; the embedded kind byte keeps an access
+// token from being used as a refresh token and vice versa. Each refresh
+// token can be redeemed once: replaying one fails with TokenReused and
+// revokes its lineage, so neither the thief nor the legitimate client can
+// refresh it any further.
+pub struct TokenPairIssuer<S> {
+    access: Branka,
+    refresh: Branka,
+    store: S,
+}
+
+impl<S: RefreshStore> TokenPairIssuer<S> {
+    // The TTL of `access` and `refresh` sets the lifetime of each token. They
+    // may share a key: the kind byte keeps the two tokens apart. Refresh
+    // times are taken from the clock of `refresh`.
+    pub fn new(access: Branka, refresh: Branka, store: S) -> Self {
+        TokenPairIssuer {
+            access,
+            refresh,
+            store,
+        }
+    }
+
+    // key: 32 bytes key.
+    // access_ttl, refresh_ttl: Time to live in seconds of each token.
+    #[cfg(feature = "std")]
+    pub fn with_key(key: &[u8], access_ttl: u32, refresh_ttl: u32, store: S) -> Self {
+        TokenPairIssuer::new(
+            Branka::new(key, access_ttl),
+            Branka::new(key, refresh_ttl),
+            store,
+        )
+    }
+
+    // Mint a pair starting a new lineage.
+    pub fn issue(&self, data: &[u8]) -> TokenPair {
+        let mut lineage = [0u8; 16];
+        OsRng.fill_bytes(&mut lineage);
+        self.mint(lineage, 0, data)
+    }
+
+    // Validate an access token and return its payload.
+    pub fn verify_access(&self, token: &str) -> Result<Vec<u8>, BrankaError> {
+        let payload = self.access.decode(token)?;
+        let (_, _, data) = split(ACCESS, &payload)?;
+        Ok(data.to_vec())
+    }
+
+    // Validate and consume a refresh token, minting the next pair in the
+    // same lineage. Fails with TokenReused if the token was already redeemed
+    // or its lineage revoked.
+    pub fn refresh(&self, token: &str) -> Result<TokenPair, BrankaError> {
+        let payload = self.refresh.decode(token)?;
+        let (lineage, generation, data) = split(REFRESH, &payload)?;
+
+        let now = self.refresh.now();
+        if !self
+            .store
+            .redeem(&lineage, generation, self.refresh.expires_at(now), now)
+        {
+            return Err(BrankaError::TokenReused);
+        }
+        let next = generation.checked_add(1).ok_or(BrankaError::TokenReused)?;
+        Ok(self.mint(lineage, next, data))
+    }
+
+    fn mint(&self, lineage: Lineage, generation: u32, data: &[u8]) -> TokenPair {
+        TokenPair {
+            access: self
+                .access
+                .encode(&join(ACCESS, &lineage, generation, data)),
+            refresh: self
+                .refresh
+                .encode(&join(REFRESH, &lineage, generation, data)),
+            lineage,
+        }
+    }
+}
+
+fn join(kind: u8, lineage: &Lineage, generation: u32, data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(PAIR_HEADER + data.len());
+    payload.push(kind);
+    payload.extend_from_slice(lineage);
+    payload.extend_from_slice(&generation.to_be_bytes());
+    payload.extend_from_slice(data);
+    payload
+}
+
+fn split(kind: u8, payload: &[u8]) -> Result<(Lineage, u32, &[u8]), BrankaError> {
+    if payload.len() < PAIR_HEADER || payload[0] != kind {
+        return Err(BrankaError::WrongTokenKind);
+    }
+    let mut lineage = [0u8; 16];
+    lineage.copy_from_slice(&payload[1..17]);
+    let generation = BigEndian::read_u32(&payload[17..PAIR_HEADER]);
+    Ok((lineage, generation, &payload[PAIR_HEADER..]))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_refresh_rotates() {
+        let issuer = TokenPairIssuer::with_key(&[5u8; 32], 300, 86400, MemoryRefreshStore::new());
+        let pair = issuer.issue(b"user:7");
+        assert_eq!(issuer.verify_access(&pair.access).unwrap(), b"user:7");
+
+        let rotated = issuer.refresh(&pair.refresh).unwrap();
+        assert_eq!(rotated.lineage, pair.lineage);
+        assert_ne!(rotated.access, pair.access);
+        assert_eq!(issuer.verify_access(&rotated.access).unwrap(), b"user:7");
+    }
+
+    #[test]
+    fn test_pair_refresh_reuse_revokes_lineage() {
+        let issuer = TokenPairIssuer::with_key(&[5u8; 32], 300, 86400, MemoryRefreshStore::new());
+        let pair = issuer.issue(b"user:7");
+        let rotated = issuer.refresh(&pair.refresh).unwrap();
+
+        assert_eq!(
+            issuer.refresh(&pair.refresh).err(),
+            Some(BrankaError::TokenReused)
+        );
+        assert_eq!(
+            issuer.refresh(&rotated.refresh).err(),
+            Some(BrankaError::TokenReused)
+        );
+
+        let other = issuer.issue(b"user:8");
+        assert!(issuer.refresh(&other.refresh).is_ok());
+    }
+
+    #[test]
+    fn test_memory_refresh_store_prunes() {
+        let store = MemoryRefreshStore::new();
+        for i in 0..MIN_PRUNE_AT as u8 {
+            assert!(store.redeem(&[i; 16], 0, 100, 0));
+        }
+        assert_eq!(store.len(), MIN_PRUNE_AT);
+
+        assert!(store.redeem(&[0xFF; 16], 0, 300, 101));
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_pair_kinds_not_interchangeable() {
+        let issuer = TokenPairIssuer::with_key(&[5u8; 32], 300, 86400, MemoryRefreshStore::new());
+        let pair = issuer.issue(b"user:7");
+        assert!(matches!(
+            issuer.refresh(&pair.access),
+            Err(BrankaError::WrongTokenKind)
+        ));
+        assert!(matches!(
+            issuer.verify_access(&pair.refresh),
+            Err(BrankaError::WrongTokenKind)
+        ));
+    }
+}
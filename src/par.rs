@@ -0,0 +1,66 @@
+use rayon::iter::ParallelIterator;
+
+use crate::{Branka, BrankaError, Payload};
+
+// Token encoding and decoding as steps of a rayon chain:
+//
+//     let claims: Vec<Result<Claims, _>> =
+//         tokens.par_iter().branka_decode::<Claims>(&branka).collect();
+//
+// Results come back in the iterator's order when it is indexed, as
+// `par_iter` over a slice or Vec is.
+pub trait ParallelBranka: ParallelIterator {
+    // Decode each token into a `T`, as decode_struct does.
+    fn branka_decode<T>(
+        self,
+        branka: &Branka,
+    ) -> impl ParallelIterator<Item = Result<T, BrankaError>>
+    where
+        Self::Item: AsRef<str>,
+        T: Payload + Send,
+    {
+        self.map(move |token| branka.decode_typed(token.as_ref()))
+    }
+
+    // Encode each item's bytes into a token.
+    fn branka_encode(self, branka: &Branka) -> impl ParallelIterator<Item = String>
+    where
+        Self::Item: AsRef<[u8]>,
+    {
+        self.map(move |item| branka.encode(item.as_ref()))
+    }
+}
+
+impl<I: ParallelIterator> ParallelBranka for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_parallel_round_trip() {
+        let branka = Branka::new(&[8u8; 32], 0).with_type_tags();
+        let ids: Vec<u64> = (0..500).collect();
+
+        let mut tokens: Vec<String> = ids
+            .par_iter()
+            .map(|id| branka.encode_struct(id).into_string())
+            .collect();
+        tokens[3] = branka.encode(b"untagged");
+        let decoded: Vec<Result<u64, BrankaError>> =
+            tokens.par_iter().branka_decode(&branka).collect();
+        assert_eq!(decoded[3], Err(BrankaError::WrongTokenKind));
+        assert_eq!(decoded[4], Ok(4));
+        assert_eq!(decoded.len(), ids.len());
+
+        let raw = Branka::new(&[8u8; 32], 0);
+        let tokens: Vec<String> = ["a", "b"].par_iter().branka_encode(&raw).collect();
+        let bytes: Vec<Vec<u8>> = tokens
+            .par_iter()
+            .branka_decode(&raw)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(bytes, [b"a".to_vec(), b"b".to_vec()]);
+    }
+}
@@ -0,0 +1,91 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::BrankaError;
+
+// Types carried in tokens. A type defines its own byte layout, and rejects
+// malformed bytes with BrankaError::InvalidPayload. With the `serde` feature,
+// serde types can implement SerdePayload instead.
+pub trait Payload: Sized {
+    fn to_payload(&self) -> Vec<u8>;
+    fn from_payload(bytes: &[u8]) -> Result<Self, BrankaError>;
+
+    // Name checked by type tags (see Branka::with_type_tags). The default is
+    // the Rust type path, which changes when the type moves; override it with
+    // a fixed name for tokens that cross service boundaries.
+    fn type_name() -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+// Type tag of `T`: the 64-bit FNV-1a hash of its type name. The tag is
+// sealed inside the token, so it only needs to tell names apart, not to
+// resist forgery.
+pub(crate) fn type_tag<T: Payload>() -> [u8; 8] {
+    let hash = T::type_name()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    hash.to_be_bytes()
+}
+
+impl Payload for Vec<u8> {
+    fn to_payload(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, BrankaError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Payload for String {
+    fn to_payload(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, BrankaError> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| BrankaError::InvalidPayload)
+    }
+}
+
+impl Payload for u64 {
+    fn to_payload(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, BrankaError> {
+        bytes
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| BrankaError::InvalidPayload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Named;
+
+    impl Payload for Named {
+        fn to_payload(&self) -> Vec<u8> {
+            Vec::new()
+        }
+
+        fn from_payload(_: &[u8]) -> Result<Self, BrankaError> {
+            Ok(Named)
+        }
+
+        fn type_name() -> &'static str {
+            "a"
+        }
+    }
+
+    #[test]
+    fn test_type_tag_is_fnv1a() {
+        assert_eq!(type_tag::<Named>(), 0xaf63dc4c8601ec8cu64.to_be_bytes());
+        assert_ne!(type_tag::<String>(), type_tag::<Vec<u8>>());
+    }
+}
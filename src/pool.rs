@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::mem;
+
+use zeroize::Zeroize;
+
+use crate::Branka;
+
+// Default cap on the size of the buffer a thread keeps between calls.
+pub const DEFAULT_BUFFER_POOL_LIMIT: usize = 64 * 1024;
+
+struct Pool {
+    buf: Vec<u8>,
+    limit: usize,
+}
+
+thread_local! {
+    static POOL: RefCell<Pool> = const {
+        RefCell::new(Pool {
+            buf: Vec::new(),
+            limit: DEFAULT_BUFFER_POOL_LIMIT,
+        })
+    };
+}
+
+// Run `f` with this thread's scratch buffer, empty.
+// The buffer is taken out of the pool for the duration of the call, so a
+// nested call (from an audit sink for instance) just gets a fresh one.
+pub(crate) fn with_scratch<R>(f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    let mut buf = POOL.with(|pool| mem::take(&mut pool.borrow_mut().buf));
+    let result = f(&mut buf);
+
+    // Decoded payloads must not linger in the pool: wipe the whole capacity,
+    // not just the current contents.
+    buf.zeroize();
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if buf.capacity() <= pool.limit && buf.capacity() > pool.buf.capacity() {
+            pool.buf = buf;
+        }
+    });
+    result
+}
+
+// Free this thread's pooled buffer.
+pub fn clear_buffer_pool() {
+    POOL.with(|pool| pool.borrow_mut().buf = Vec::new());
+}
+
+// Largest buffer, in bytes, this thread keeps between calls; larger ones
+// (from tokens bigger than usual) are freed after use.
+pub fn set_buffer_pool_limit(limit: usize) {
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        pool.limit = limit;
+        if pool.buf.capacity() > limit {
+            pool.buf = Vec::new();
+        }
+    });
+}
+
+// Capacity of this thread's pooled buffer.
+pub fn buffer_pool_size() -> usize {
+    POOL.with(|pool| pool.borrow().buf.capacity())
+}
+
+impl Branka {
+    // Work in a per-thread scratch buffer reused across calls, so encode and
+    // decode only allocate the token or payload they return.
+    pub fn with_buffer_pool(mut self) -> Branka {
+        self.pooled = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_pool() {
+        let branka = Branka::new(&[6u8; 32], 0).with_buffer_pool();
+        let token = branka.encode(b"pooled");
+        assert!(buffer_pool_size() >= 6);
+        assert_eq!(branka.decode(&token).unwrap(), b"pooled");
+
+        clear_buffer_pool();
+        assert_eq!(buffer_pool_size(), 0);
+        set_buffer_pool_limit(8);
+        branka.encode(b"pooled");
+        assert_eq!(buffer_pool_size(), 0);
+        set_buffer_pool_limit(DEFAULT_BUFFER_POOL_LIMIT);
+    }
+}
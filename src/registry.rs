@@ -0,0 +1,126 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::payload::type_tag;
+use crate::{Branka, BrankaError, Payload};
+
+type Decoder<E> = Box<dyn Fn(&[u8]) -> Result<E, BrankaError> + Send + Sync>;
+
+struct Entry<E> {
+    discriminant: u8,
+    tag: [u8; 8],
+    decode: Decoder<E>,
+}
+
+// The payload types one endpoint accepts, each under a fixed discriminant
+// byte. `E` is the caller's enum with one variant per registered type.
+//
+//     let registry = Registry::new()
+//         .register(1, Kind::Invite)
+//         .register(2, Kind::Reset);
+//     match branka.decode_any(&registry, &token)? { ... }
+pub struct Registry<E> {
+    entries: Vec<Entry<E>>,
+}
+
+impl<E> Default for Registry<E> {
+    fn default() -> Self {
+        Registry {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<E> Registry<E> {
+    pub fn new() -> Registry<E> {
+        Registry::default()
+    }
+
+    // Accept `T` under `discriminant`, wrapping decoded values with
+    // `variant`. Discriminants are part of the token format: never reuse one
+    // for a different type.
+    pub fn register<T: Payload + 'static>(mut self, discriminant: u8, variant: fn(T) -> E) -> Self
+    where
+        E: 'static,
+    {
+        let tag = type_tag::<T>();
+        assert!(
+            self.entries
+                .iter()
+                .all(|entry| entry.discriminant != discriminant && entry.tag != tag),
+            "type or discriminant registered twice"
+        );
+        self.entries.push(Entry {
+            discriminant,
+            tag,
+            decode: Box::new(move |bytes| T::from_payload(bytes).map(variant)),
+        });
+        self
+    }
+
+    fn discriminant<T: Payload>(&self) -> Option<u8> {
+        let tag = type_tag::<T>();
+        self.entries
+            .iter()
+            .find(|entry| entry.tag == tag)
+            .map(|entry| entry.discriminant)
+    }
+}
+
+impl Branka {
+    // Encode `value` prefixed with its discriminant in `registry`.
+    // Panics if `T` is not registered.
+    pub fn encode_any<E, T: Payload>(&self, registry: &Registry<E>, value: &T) -> String {
+        let discriminant = registry.discriminant::<T>().expect("type not registered");
+        let mut payload = value.to_payload();
+        payload.insert(0, discriminant);
+        self.encode(&payload)
+    }
+
+    // Decode a token produced by encode_any into the matching variant.
+    // Unknown discriminants fail with WrongTokenKind.
+    pub fn decode_any<E>(&self, registry: &Registry<E>, data: &str) -> Result<E, BrankaError> {
+        let payload = self.decode(data)?;
+        let (discriminant, bytes) = payload.split_first().ok_or(BrankaError::WrongTokenKind)?;
+        let entry = registry
+            .entries
+            .iter()
+            .find(|entry| entry.discriminant == *discriminant)
+            .ok_or(BrankaError::WrongTokenKind)?;
+        (entry.decode)(bytes)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Kind {
+        Name(String),
+        Count(u64),
+    }
+
+    #[test]
+    fn test_registry_dispatch() {
+        let branka = Branka::new(&[8u8; 32], 0);
+        let registry = Registry::new()
+            .register(1, Kind::Name)
+            .register(2, Kind::Count);
+
+        let token = branka.encode_any(&registry, &String::from("ada"));
+        assert_eq!(
+            branka.decode_any(&registry, &token),
+            Ok(Kind::Name("ada".into()))
+        );
+        let token = branka.encode_any(&registry, &7u64);
+        assert_eq!(branka.decode_any(&registry, &token), Ok(Kind::Count(7)));
+
+        let other = Registry::new().register(3, Kind::Count);
+        assert_eq!(
+            branka.decode_any(&other, &token),
+            Err(BrankaError::WrongTokenKind)
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Branka, BrankaError, UsedTokenStore};
+
+// Anti-replay decoding for webhook and callback tokens.
+// Tokens are only accepted within `window` seconds of being issued, and each
+// one only once inside that window: the store only has to remember the nonces
+// seen during the last `window` seconds.
+pub struct ReplayGuard<S> {
+    branka: Branka,
+    store: S,
+    window: u32,
+}
+
+impl<S: UsedTokenStore> ReplayGuard<S> {
+    pub fn new(branka: Branka, store: S, window: u32) -> Self {
+        ReplayGuard {
+            branka,
+            store,
+            window,
+        }
+    }
+
+    pub fn encode(&self, data: &[u8]) -> String {
+        self.branka.encode(data)
+    }
+
+    pub fn decode(&self, token: &str) -> Result<Vec<u8>, BrankaError> {
+        self.decode_aad(token, &[])
+    }
+
+    // Like decode, for tokens from Branka::encode_aad.
+    pub fn decode_aad(&self, token: &str, aad: &[u8]) -> Result<Vec<u8>, BrankaError> {
+        let decoded = self.branka.decode_full_aad(token, aad)?;
+        let now = self.branka.now();
+        if now.abs_diff(decoded.timestamp) > self.window {
+            return Err(BrankaError::Expired);
+        }
+
+        let forget_at = decoded.timestamp.saturating_add(self.window);
+        if !self.store.mark_used(&decoded.nonce, forget_at, now) {
+            return Err(BrankaError::TokenReused);
+        }
+        Ok(decoded.payload)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::MemoryUsedTokenStore;
+
+    #[test]
+    fn test_replay_rejected() {
+        let guard = ReplayGuard::new(Branka::new(&[8u8; 32], 0), MemoryUsedTokenStore::new(), 300);
+        let token = guard.encode(b"webhook");
+
+        assert_eq!(guard.decode(&token).unwrap(), b"webhook");
+        assert!(matches!(
+            guard.decode(&token),
+            Err(BrankaError::TokenReused)
+        ));
+    }
+}
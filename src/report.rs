@@ -0,0 +1,158 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{base62, Branka, BrankaError, BASE62};
+
+// Why a token failed to decode, with details that are safe to log:
+// only lengths, offsets and header fields, never plaintext or key material.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeFailure {
+    // The token is not valid base62; `offset` is the first offending byte.
+    BadEncoding { token_len: usize, offset: usize },
+    // The token exceeds the configured maximum length.
+    TooLong { token_len: usize, max_len: usize },
+    // The decoded token is shorter than a header plus tag.
+    Truncated { decoded_len: usize, min_len: usize },
+    UnknownVersion { version: u8 },
+    // Authentication failed: wrong key, or the token was modified.
+    WrongKeyOrTampered { ciphertext_len: usize },
+    Expired { issued_at: u32, expired_at: u32 },
+    // Rejected by a higher-level check (revocation, reuse, ...).
+    Rejected(BrankaError),
+}
+
+impl fmt::Display for DecodeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeFailure::BadEncoding { token_len, offset } => write!(
+                f,
+                "invalid base62 at offset {} of {}-byte token",
+                offset, token_len
+            ),
+            DecodeFailure::TooLong { token_len, max_len } => write!(
+                f,
+                "token too long: {} bytes, at most {} accepted",
+                token_len, max_len
+            ),
+            DecodeFailure::Truncated {
+                decoded_len,
+                min_len,
+            } => write!(
+                f,
+                "token truncated: {} bytes, at least {} expected",
+                decoded_len, min_len
+            ),
+            DecodeFailure::UnknownVersion { version } => {
+                write!(f, "unknown version byte 0x{:02X}", version)
+            }
+            DecodeFailure::WrongKeyOrTampered { ciphertext_len } => write!(
+                f,
+                "authentication failed ({}-byte ciphertext): wrong key or tampered token",
+                ciphertext_len
+            ),
+            DecodeFailure::Expired {
+                issued_at,
+                expired_at,
+            } => write!(f, "token issued at {} expired at {}", issued_at, expired_at),
+            DecodeFailure::Rejected(err) => write!(f, "token rejected: {:?}", err),
+        }
+    }
+}
+
+impl Branka {
+    // Decode a token, explaining any failure with a DecodeFailure report.
+    pub fn decode_with_report(&self, data: &str) -> Result<Vec<u8>, DecodeFailure> {
+        self.decode(data).map_err(|err| self.diagnose(data, err))
+    }
+
+    pub(crate) fn diagnose(&self, data: &str, err: BrankaError) -> DecodeFailure {
+        match err {
+            BrankaError::InvalidBase62 => DecodeFailure::BadEncoding {
+                token_len: data.len(),
+                offset: data
+                    .bytes()
+                    .position(|b| !BASE62.as_bytes().contains(&b))
+                    .unwrap_or(0),
+            },
+            BrankaError::TokenTooLong => DecodeFailure::TooLong {
+                token_len: data.len(),
+                max_len: self.max_token_len,
+            },
+            err => match decoded(data) {
+                Ok(token) => self.diagnose_decoded(&token, err),
+                Err(err) => self.diagnose(data, err),
+            },
+        }
+    }
+
+    // Details of a failure read from the binary `token`. Errors the token
+    // doesn't explain (e.g. it is too short to hold the field) are reported
+    // as is.
+    fn diagnose_decoded(&self, token: &[u8], err: BrankaError) -> DecodeFailure {
+        let min_len = 29 + 16;
+        match err {
+            BrankaError::InvalidDataLength => DecodeFailure::Truncated {
+                decoded_len: token.len(),
+                min_len,
+            },
+            BrankaError::InvalidVersion => match token.first() {
+                Some(&version) => DecodeFailure::UnknownVersion { version },
+                None => DecodeFailure::Rejected(err),
+            },
+            BrankaError::InvalidData => DecodeFailure::WrongKeyOrTampered {
+                ciphertext_len: token.len().saturating_sub(min_len),
+            },
+            BrankaError::Expired => match token.get(1..5) {
+                Some(timestamp) => {
+                    let issued_at = BigEndian::read_u32(timestamp);
+                    DecodeFailure::Expired {
+                        issued_at,
+                        expired_at: self.expires_at(issued_at),
+                    }
+                }
+                None => DecodeFailure::Rejected(err),
+            },
+            err => DecodeFailure::Rejected(err),
+        }
+    }
+}
+
+// Decode the base62 text of a token with the codec decode uses.
+fn decoded(data: &str) -> Result<Vec<u8>, BrankaError> {
+    let mut token = Vec::new();
+    base62::decode_into(data, &mut token).map_err(|_| BrankaError::InvalidBase62)?;
+    Ok(token)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_categories() {
+        let branka = Branka::new(&[2u8; 32], 0);
+        let token = branka.encode(b"secret payload");
+
+        assert_eq!(
+            branka.decode_with_report("abc-def").unwrap_err(),
+            DecodeFailure::BadEncoding {
+                token_len: 7,
+                offset: 3
+            }
+        );
+        assert!(matches!(
+            branka.decode_with_report("abc").unwrap_err(),
+            DecodeFailure::Truncated { min_len: 45, .. }
+        ));
+
+        let other = Branka::new(&[3u8; 32], 0);
+        let failure = other.decode_with_report(&token).unwrap_err();
+        assert_eq!(
+            failure,
+            DecodeFailure::WrongKeyOrTampered { ciphertext_len: 14 }
+        );
+        assert!(!failure.to_string().contains("secret"));
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use crate::{Branka, BrankaError, TokenId};
+
+// Tokens killed before their natural expiry, keyed by token id.
+pub trait RevocationStore {
+    // Revoke a token until `expires_at` (unix time); past that point the
+    // token is expired anyway and the entry can be dropped.
+    // `now` is the current time according to the revoking Branka's clock.
+    // Fails with StoreFailure if the revocation could not be recorded.
+    fn revoke(&self, id: &TokenId, expires_at: u32, now: u32) -> Result<(), BrankaError>;
+    fn is_revoked(&self, id: &TokenId) -> bool;
+}
+
+// Thread-safe in-process revocation list.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct MemoryRevocationStore {
+    revoked: Mutex<HashMap<TokenId, u32>>,
+}
+
+#[cfg(feature = "std")]
+impl MemoryRevocationStore {
+    pub fn new() -> MemoryRevocationStore {
+        MemoryRevocationStore::default()
+    }
+
+    // Drop entries for tokens that have expired at `now`.
+    pub fn prune(&self, now: u32) {
+        self.revoked
+            .lock()
+            .unwrap()
+            .retain(|_, expires_at| *expires_at >= now);
+    }
+
+    pub fn len(&self) -> usize {
+        self.revoked.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "std")]
+impl RevocationStore for MemoryRevocationStore {
+    fn revoke(&self, id: &TokenId, expires_at: u32, now: u32) -> Result<(), BrankaError> {
+        self.prune(now);
+        self.revoked.lock().unwrap().insert(*id, expires_at);
+        Ok(())
+    }
+
+    fn is_revoked(&self, id: &TokenId) -> bool {
+        self.revoked.lock().unwrap().contains_key(id)
+    }
+}
+
+impl Branka {
+    // Decode a token, rejecting it if it has been revoked.
+    pub fn decode_checked<R: RevocationStore>(
+        &self,
+        data: &str,
+        store: &R,
+    ) -> Result<Vec<u8>, BrankaError> {
+        let decoded = self.decode_full(data)?;
+        if store.is_revoked(&decoded.nonce) {
+            return Err(BrankaError::Revoked);
+        }
+        Ok(decoded.payload)
+    }
+
+    // Revoke a valid token. Invalid or expired tokens are rejected, as are
+    // revocations the store failed to record.
+    pub fn revoke<R: RevocationStore>(&self, data: &str, store: &R) -> Result<(), BrankaError> {
+        let decoded = self.decode_full(data)?;
+        store.revoke(
+            &decoded.nonce,
+            self.expires_at(decoded.timestamp),
+            self.now(),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revoked_token_rejected() {
+        let branka = Branka::new(&[4u8; 32], 3600);
+        let store = MemoryRevocationStore::new();
+        let token = branka.encode(b"payload");
+        let other = branka.encode(b"payload");
+
+        assert!(branka.decode_checked(&token, &store).is_ok());
+        branka.revoke(&token, &store).unwrap();
+        assert!(matches!(
+            branka.decode_checked(&token, &store),
+            Err(BrankaError::Revoked)
+        ));
+        assert!(branka.decode_checked(&other, &store).is_ok());
+
+        struct Failing;
+        impl RevocationStore for Failing {
+            fn revoke(&self, _: &TokenId, _: u32, _: u32) -> Result<(), BrankaError> {
+                Err(BrankaError::StoreFailure)
+            }
+            fn is_revoked(&self, _: &TokenId) -> bool {
+                false
+            }
+        }
+        assert_eq!(
+            branka.revoke(&other, &Failing),
+            Err(BrankaError::StoreFailure)
+        );
+    }
+
+    #[test]
+    fn test_prune_expired_entries() {
+        let store = MemoryRevocationStore::new();
+        store.revoke(&[1u8; 24], 10, 0).unwrap();
+        store.revoke(&[2u8; 24], 30, 0).unwrap();
+        store.prune(20);
+        assert_eq!(store.len(), 1);
+        assert!(store.is_revoked(&[2u8; 24]));
+    }
+}
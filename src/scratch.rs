@@ -0,0 +1,87 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use zeroize::Zeroize;
+
+use crate::{Branka, BrankaError};
+
+// Caller-owned working memory for encode and decode. Results are borrowed
+// from it, so a server can keep one per request or per worker and let it
+// absorb every token-related allocation.
+#[derive(Default)]
+pub struct Scratch {
+    buf: Vec<u8>,
+    text: String,
+}
+
+impl Scratch {
+    pub fn new() -> Scratch {
+        Scratch::default()
+    }
+
+    // Preallocate for payloads of up to `payload_len` bytes.
+    pub fn with_capacity(payload_len: usize) -> Scratch {
+        let token_len = payload_len + crate::TOKEN_OVERHEAD;
+        Scratch {
+            buf: Vec::with_capacity(token_len),
+            text: String::with_capacity(token_len * 4 / 3 + 1),
+        }
+    }
+
+    // Wipe the last decoded payload, including any of it left in the spare
+    // capacity.
+    pub fn clear(&mut self) {
+        self.buf.zeroize();
+        self.text.clear();
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        self.buf.zeroize();
+    }
+}
+
+impl Branka {
+    // Encode `data` into `scratch`, returning the token borrowed from it.
+    pub fn encode_with<'a>(
+        &self,
+        data: &[u8],
+        scratch: &'a mut Scratch,
+    ) -> Result<&'a str, BrankaError> {
+        self.encode_into(data, &mut scratch.buf, &mut scratch.text)?;
+        Ok(&scratch.text)
+    }
+
+    // Decode `data` into `scratch`, returning the payload borrowed from it.
+    pub fn decode_with<'a>(
+        &self,
+        data: &str,
+        scratch: &'a mut Scratch,
+    ) -> Result<&'a [u8], BrankaError> {
+        self.decode_into(data, &mut scratch.buf)?;
+        Ok(&scratch.buf)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_round_trip() {
+        let branka = Branka::new(&[8u8; 32], 0);
+        let mut scratch = Scratch::with_capacity(64);
+        let token = branka
+            .encode_with(b"scratch", &mut scratch)
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            branka.decode_with(&token, &mut scratch).unwrap(),
+            b"scratch"
+        );
+
+        scratch.clear();
+        assert!(branka.decode_with("0", &mut scratch).is_err());
+    }
+}
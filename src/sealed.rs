@@ -0,0 +1,113 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{Branka, BrankaError, Clock};
+
+// First line of a sealed config file.
+pub const SEALED_CONFIG_HEADER: &str = "# branka-sealed v1";
+
+// Sealed values never expire, and carry timestamp 0 rather than revealing
+// when they were sealed. They come from the operator's own files, so they
+// are opened whatever their length: a certificate chain or a PEM key must
+// not seal fine and then fail to open.
+struct Epoch;
+
+impl Clock for Epoch {
+    fn now(&self) -> u32 {
+        0
+    }
+}
+
+fn sealer(key: &[u8]) -> Branka {
+    Branka::new_with_clock(key, 0, Arc::new(Epoch)).with_max_token_len(usize::MAX)
+}
+
+// Encrypt a secret at rest (a config value, a .env entry). `purpose` (e.g.
+// the setting name) is bound as associated data, so a sealed value cannot
+// be moved to another setting.
+pub fn seal_value(key: &[u8], purpose: &str, value: &[u8]) -> Result<String, BrankaError> {
+    sealer(key).encode_aad(value, purpose.as_bytes())
+}
+
+// Decrypt a value from seal_value. A wrong key or purpose fails with
+// InvalidData.
+pub fn open_value(key: &[u8], purpose: &str, sealed: &str) -> Result<Vec<u8>, BrankaError> {
+    sealer(key).decode_aad(sealed, purpose.as_bytes())
+}
+
+// Seal settings into a config file: SEALED_CONFIG_HEADER, then one
+// `NAME=<sealed value>` line per setting, each value sealed with its name
+// as purpose. Panics if a name is empty or contains `=` or a line break.
+pub fn seal_config(key: &[u8], settings: &[(&str, &[u8])]) -> Result<String, BrankaError> {
+    let mut file = String::from(SEALED_CONFIG_HEADER);
+    file.push('\n');
+    for (name, value) in settings {
+        assert!(
+            !name.is_empty() && !name.contains(['=', '\n', '\r']),
+            "invalid setting name"
+        );
+        file.push_str(name);
+        file.push('=');
+        file.push_str(&seal_value(key, name, value)?);
+        file.push('\n');
+    }
+    Ok(file)
+}
+
+// Open every setting of a sealed config file, in file order. Blank lines
+// and `#` comments are skipped; a missing header fails with InvalidVersion
+// and a malformed line with InvalidPayload.
+pub fn open_config(key: &[u8], file: &str) -> Result<Vec<(String, Vec<u8>)>, BrankaError> {
+    let mut lines = file.lines();
+    if lines.next().map(str::trim_end) != Some(SEALED_CONFIG_HEADER) {
+        return Err(BrankaError::InvalidVersion);
+    }
+    let mut settings = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, sealed) = line.split_once('=').ok_or(BrankaError::InvalidPayload)?;
+        settings.push((String::from(name), open_value(key, name, sealed)?));
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sealed_values() {
+        let key = [9u8; 32];
+        let sealed = seal_value(&key, "DATABASE_URL", b"postgres://db").unwrap();
+        assert_eq!(
+            open_value(&key, "DATABASE_URL", &sealed).unwrap(),
+            b"postgres://db"
+        );
+        assert_eq!(
+            open_value(&key, "API_TOKEN", &sealed),
+            Err(BrankaError::InvalidData)
+        );
+
+        let file = seal_config(&key, &[("A", b"1"), ("B", b"two")]).unwrap();
+        assert!(file.starts_with(SEALED_CONFIG_HEADER));
+        let settings = open_config(&key, &file).unwrap();
+        assert_eq!(
+            settings,
+            vec![
+                ("A".to_string(), b"1".to_vec()),
+                ("B".to_string(), b"two".to_vec())
+            ]
+        );
+
+        let swapped = file.replacen("A=", "C=", 1);
+        assert_eq!(open_config(&key, &swapped), Err(BrankaError::InvalidData));
+
+        let pem = [b'-'; 8 * 1024];
+        let sealed = seal_value(&key, "TLS_KEY", &pem).unwrap();
+        assert_eq!(open_value(&key, "TLS_KEY", &sealed).unwrap(), pem);
+    }
+}
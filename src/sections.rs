@@ -0,0 +1,109 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{Branka, BrankaError, Payload};
+
+// A decoded multi-section token. Sections are located once on decode;
+// parsing one (with its own Payload type) leaves the others untouched, so a
+// middleware reading the public claims does not pay for the rest.
+#[derive(Debug, Clone)]
+pub struct Sections {
+    payload: Vec<u8>,
+    ranges: Vec<Range<usize>>,
+}
+
+impl Sections {
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.ranges
+            .get(index)
+            .map(|range| &self.payload[range.clone()])
+    }
+
+    // Parse section `index` as a `T`. A missing section fails with
+    // InvalidPayload.
+    pub fn parse<T: Payload>(&self, index: usize) -> Result<T, BrankaError> {
+        T::from_payload(self.get(index).ok_or(BrankaError::InvalidPayload)?)
+    }
+}
+
+impl Branka {
+    // Encode independent sections (public claims, private claims, routing
+    // hints, ...) into one token. At most 255 sections.
+    //
+    // Payload: Count || (Length)* || (Section)*, lengths as u32 BE.
+    pub fn encode_sections(&self, sections: &[&[u8]]) -> Result<String, BrankaError> {
+        let count = u8::try_from(sections.len()).map_err(|_| BrankaError::InvalidPayload)?;
+        let total: usize = sections.iter().map(|section| section.len()).sum();
+        let mut payload = Vec::with_capacity(1 + 4 * sections.len() + total);
+        payload.push(count);
+        for section in sections {
+            let len = u32::try_from(section.len()).map_err(|_| BrankaError::InvalidPayload)?;
+            payload.extend_from_slice(&len.to_be_bytes());
+        }
+        for section in sections {
+            payload.extend_from_slice(section);
+        }
+        self.try_encode(&payload)
+    }
+
+    pub fn decode_sections(&self, data: &str) -> Result<Sections, BrankaError> {
+        let payload = self.decode(data)?;
+        let (&count, rest) = payload.split_first().ok_or(BrankaError::InvalidPayload)?;
+        let table_len = 4 * usize::from(count);
+        if rest.len() < table_len {
+            return Err(BrankaError::InvalidPayload);
+        }
+
+        let mut ranges = Vec::with_capacity(usize::from(count));
+        let mut start = 1 + table_len;
+        for len in rest[..table_len].chunks(4) {
+            let end = start
+                .checked_add(BigEndian::read_u32(len) as usize)
+                .filter(|&end| end <= payload.len())
+                .ok_or(BrankaError::InvalidPayload)?;
+            ranges.push(start..end);
+            start = end;
+        }
+        if start != payload.len() {
+            return Err(BrankaError::InvalidPayload);
+        }
+        Ok(Sections { payload, ranges })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let branka = Branka::new(&[4u8; 32], 0);
+        let token = branka
+            .encode_sections(&[b"public", b"", &7u64.to_be_bytes()])
+            .unwrap();
+
+        let sections = branka.decode_sections(&token).unwrap();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections.get(0).unwrap(), b"public");
+        assert_eq!(sections.get(1).unwrap(), b"");
+        assert_eq!(sections.parse::<u64>(2).unwrap(), 7);
+        assert_eq!(sections.get(3), None);
+
+        let plain = branka.encode(b"\x01\x00\x00\x00\x09short");
+        assert_eq!(
+            branka.decode_sections(&plain).unwrap_err(),
+            BrankaError::InvalidPayload
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::vec::Vec;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{BrankaError, Payload};
+
+// Opt a serde type into Payload, carried as compact JSON, so encode_struct
+// and decode_struct take it without a hand-written byte layout:
+//
+//     #[derive(Serialize, Deserialize)]
+//     struct Claims { sub: String, exp: u64 }
+//     impl SerdePayload for Claims {}
+//
+// Override type_name here for tokens that cross service boundaries (see
+// Payload::type_name).
+pub trait SerdePayload: Serialize + DeserializeOwned {
+    fn type_name() -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+impl<T: SerdePayload> Payload for T {
+    fn to_payload(&self) -> Vec<u8> {
+        // A Serialize impl may fail (a map with non-string keys, a custom
+        // impl returning an error); that is a bug in the type, not bad input.
+        serde_json::to_vec(self).expect("SerdePayload type failed to serialize")
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, BrankaError> {
+        serde_json::from_slice(bytes).map_err(|_| BrankaError::InvalidPayload)
+    }
+
+    fn type_name() -> &'static str {
+        <T as SerdePayload>::type_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Branka, Token};
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        admin: bool,
+    }
+
+    impl SerdePayload for Claims {
+        fn type_name() -> &'static str {
+            "claims"
+        }
+    }
+
+    #[test]
+    fn test_serde_struct_round_trip() {
+        let branka = Branka::new(&[6u8; 32], 0).with_type_tags();
+        let claims = Claims {
+            sub: "user-1".into(),
+            admin: false,
+        };
+        let token = branka.encode_struct(&claims);
+        assert_eq!(branka.decode_struct(&token), Ok(claims));
+
+        let forged = Token::<Claims>::new(branka.encode_struct(&7u64).into_string());
+        assert_eq!(
+            branka.decode_struct(&forged),
+            Err(BrankaError::WrongTokenKind)
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{BrankaError, Payload};
+
+// Payload layout version written by this release. Decoders accept every
+// version up to this one; later versions may append fields.
+const SERVICE_TOKEN_VERSION: u8 = 1;
+
+// Operations checked by ServiceToken::crud_allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crud {
+    Create,
+    Read,
+    Update,
+    Delete,
+}
+
+impl Crud {
+    fn as_str(self) -> &'static str {
+        match self {
+            Crud::Create => "create",
+            Crud::Read => "read",
+            Crud::Update => "update",
+            Crud::Delete => "delete",
+        }
+    }
+}
+
+// Claims of a service-to-service token: the calling service and the scopes
+// it was granted, as `resource:operation` strings (`billing:read`). Granted
+// scopes may be globs, see scope_matches.
+// Carried with Branka::encode_struct / decode_struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceToken {
+    pub service: String,
+    pub scopes: Vec<String>,
+}
+
+impl ServiceToken {
+    pub fn new(service: &str) -> ServiceToken {
+        ServiceToken {
+            service: String::from(service),
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        self.scopes.push(String::from(scope));
+        self
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|granted| scope_matches(granted, scope))
+    }
+
+    // Whether the token grants `operation` on `resource`, as the scope
+    // `<resource>:<operation>` (`invoices:update`).
+    pub fn crud_allows(&self, resource: &str, operation: Crud) -> bool {
+        self.has_scope(&format!("{}:{}", resource, operation.as_str()))
+    }
+}
+
+// Payload: Version || Service || Scope count || Scope*, strings prefixed
+// with their u16 BE length and the count as u16 BE. Panics on more than
+// 65535 scopes, or strings longer than 65535 bytes.
+impl Payload for ServiceToken {
+    fn to_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.push(SERVICE_TOKEN_VERSION);
+        write_str(&mut payload, &self.service);
+        let count = u16::try_from(self.scopes.len()).expect("too many service token scopes");
+        payload.extend_from_slice(&count.to_be_bytes());
+        for scope in &self.scopes {
+            write_str(&mut payload, scope);
+        }
+        payload
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, BrankaError> {
+        let (&version, mut bytes) = bytes.split_first().ok_or(BrankaError::InvalidPayload)?;
+        if version == 0 || version > SERVICE_TOKEN_VERSION {
+            return Err(BrankaError::InvalidPayload);
+        }
+        let service = read_str(&mut bytes)?;
+        let count = read_u16(&mut bytes)?;
+        let scopes = (0..count)
+            .map(|_| read_str(&mut bytes))
+            .collect::<Result<_, _>>()?;
+        if !bytes.is_empty() {
+            return Err(BrankaError::InvalidPayload);
+        }
+        Ok(ServiceToken { service, scopes })
+    }
+
+    fn type_name() -> &'static str {
+        "branka:service-token"
+    }
+}
+
+// Whether the granted scope `pattern` covers `scope`. Scopes are split into
+// segments by ':' and '/'; in the pattern `*` matches any run of characters
+// within one segment and `**` any run across segments, so `billing:*`
+// covers `billing:read` but not `billing:read:own`, and `org/123/**`
+// covers everything below `org/123/`. Every other character matches itself.
+pub fn scope_matches(pattern: &str, scope: &str) -> bool {
+    glob(pattern.as_bytes(), scope.as_bytes())
+}
+
+fn is_separator(byte: u8) -> bool {
+    byte == b':' || byte == b'/'
+}
+
+// Iterative wildcard match. On a mismatch, the last `*` swallows one more
+// byte of its segment and matching resumes after it; once it would have to
+// swallow a separator, the segments cannot line up and only the last `**`
+// can swallow more. No other backtracking is needed, so the time is linear
+// in practice, and never exponential in the number of stars.
+fn glob(pattern: &[u8], scope: &[u8]) -> bool {
+    // Where matching resumes in the pattern after each star, and how much of
+    // the scope is matched once it has swallowed one more byte.
+    let mut star: Option<(usize, usize)> = None;
+    let mut globstar: Option<(usize, usize)> = None;
+    let (mut p, mut s) = (0, 0);
+    while s < scope.len() {
+        match pattern.get(p..).unwrap_or_default() {
+            [b'*', b'*', ..] => {
+                p += 2;
+                globstar = Some((p, s + 1));
+                star = None;
+            }
+            [b'*', ..] => {
+                p += 1;
+                star = Some((p, s + 1));
+            }
+            [expected, ..] if *expected == scope[s] => {
+                p += 1;
+                s += 1;
+            }
+            _ => match (star, globstar) {
+                (Some((resume, end)), _) if !is_separator(scope[end - 1]) => {
+                    star = Some((resume, end + 1));
+                    (p, s) = (resume, end);
+                }
+                (_, Some((resume, end))) => {
+                    globstar = Some((resume, end + 1));
+                    star = None;
+                    (p, s) = (resume, end);
+                }
+                _ => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&byte| byte == b'*')
+}
+
+// Panics on strings longer than 65535 bytes.
+fn write_str(payload: &mut Vec<u8>, text: &str) {
+    let len = u16::try_from(text.len()).expect("service token string too long");
+    payload.extend_from_slice(&len.to_be_bytes());
+    payload.extend_from_slice(text.as_bytes());
+}
+
+fn read_u16(bytes: &mut &[u8]) -> Result<usize, BrankaError> {
+    let (value, rest) = bytes
+        .split_first_chunk::<2>()
+        .ok_or(BrankaError::InvalidPayload)?;
+    *bytes = rest;
+    Ok(usize::from(BigEndian::read_u16(value)))
+}
+
+fn read_str(bytes: &mut &[u8]) -> Result<String, BrankaError> {
+    let len = read_u16(bytes)?;
+    let (text, rest) = bytes
+        .split_at_checked(len)
+        .ok_or(BrankaError::InvalidPayload)?;
+    *bytes = rest;
+    String::from_utf8(text.to_vec()).map_err(|_| BrankaError::InvalidPayload)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Branka;
+
+    #[test]
+    fn test_service_token() {
+        let branka = Branka::new(&[6u8; 32], 0).with_type_tags();
+        let token = branka.encode_struct(
+            &ServiceToken::new("checkout")
+                .with_scope("billing:read")
+                .with_scope("invoices:update"),
+        );
+
+        let claims = branka.decode_struct(&token).unwrap();
+        assert_eq!(claims.service, "checkout");
+        assert!(claims.has_scope("billing:read"));
+        assert!(!claims.has_scope("billing:write"));
+        assert!(claims.crud_allows("invoices", Crud::Update));
+        assert!(!claims.crud_allows("invoices", Crud::Delete));
+        assert!(!claims.crud_allows("invoice", Crud::Update));
+
+        let wildcard = ServiceToken::new("admin").with_scope("invoices:*");
+        assert!(wildcard.crud_allows("invoices", Crud::Delete));
+        assert!(!wildcard.crud_allows("billing", Crud::Read));
+
+        assert_eq!(
+            ServiceToken::from_payload(&[2, 0, 0, 0, 0]),
+            Err(BrankaError::InvalidPayload)
+        );
+    }
+
+    #[test]
+    fn test_scope_matches() {
+        assert!(scope_matches("billing:read", "billing:read"));
+        assert!(!scope_matches("billing:read", "billing:reader"));
+        assert!(scope_matches("billing:*", "billing:read"));
+        assert!(scope_matches("billing:*", "billing:"));
+        assert!(!scope_matches("billing:*", "billing:read:own"));
+        assert!(!scope_matches("billing:*", "billing"));
+        assert!(scope_matches("*:read", "invoices:read"));
+        assert!(scope_matches("inv*:read", "invoices:read"));
+        assert!(!scope_matches("*", "org/123"));
+        assert!(scope_matches("org/123/**", "org/123/projects/9"));
+        assert!(scope_matches("org/123/**", "org/123/"));
+        assert!(!scope_matches("org/123/**", "org/1234/projects"));
+        assert!(!scope_matches("org/123/**", "org/123"));
+        assert!(scope_matches("org/**/read", "org/123/projects/read"));
+        assert!(scope_matches("**", "anything:at/all"));
+        assert!(!scope_matches("", "billing:read"));
+        assert!(scope_matches("**:*d", "org/123:read"));
+        assert!(!scope_matches("**/*:x", "org/a:b/c"));
+
+        // Exponential for a backtracking matcher.
+        let scope = "a".repeat(60);
+        assert!(!scope_matches(&format!("{}b", "a*".repeat(30)), &scope));
+        assert!(!scope_matches(&format!("{}b", "a**".repeat(30)), &scope));
+    }
+}
@@ -0,0 +1,303 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use byteorder::{BigEndian, ByteOrder};
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+
+use crate::{Branka, BrankaError};
+
+pub type SessionId = [u8; 16];
+
+// Session id || Creation timestamp
+const SESSION_HEADER: usize = 16 + 4;
+
+// Server-side record of live sessions, used for idle timeouts and invalidation.
+// `expires_at` is the time after which a session can no longer be verified
+// (the earlier of its idle and absolute deadlines), so stores may drop it
+// from then on.
+pub trait SessionStore {
+    // Register a new session.
+    fn insert(&self, id: SessionId, now: u32, expires_at: u32);
+    // Record activity on a session and move its deadline to `expires_at`,
+    // returning when it was last seen, or None if the session is unknown
+    // (never issued, invalidated or pruned).
+    fn touch(&self, id: &SessionId, now: u32, expires_at: u32) -> Option<u32>;
+    fn remove(&self, id: &SessionId);
+    // Forget sessions whose deadline is before `now`.
+    fn prune(&self, now: u32);
+}
+
+// Number of sessions a MemorySessionStore holds before its first prune.
+#[cfg(feature = "std")]
+const MIN_PRUNE_AT: usize = 64;
+
+// In-process session store, suitable for a single instance.
+// Expired sessions are pruned on insert whenever the store has doubled in
+// size since the last prune, so abandoned sessions don't accumulate.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<Sessions>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Sessions {
+    // Last seen, expires at.
+    live: HashMap<SessionId, (u32, u32)>,
+    prune_at: usize,
+}
+
+#[cfg(feature = "std")]
+impl MemorySessionStore {
+    pub fn new() -> MemorySessionStore {
+        MemorySessionStore::default()
+    }
+
+    // Number of sessions held, expired ones included until pruned.
+    pub fn len(&self) -> usize {
+        self.sessions.lock().unwrap().live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "std")]
+impl SessionStore for MemorySessionStore {
+    fn insert(&self, id: SessionId, now: u32, expires_at: u32) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.live.len() >= sessions.prune_at {
+            sessions
+                .live
+                .retain(|_, &mut (_, expires_at)| expires_at >= now);
+            sessions.prune_at = (sessions.live.len() * 2).max(MIN_PRUNE_AT);
+        }
+        sessions.live.insert(id, (now, expires_at));
+    }
+
+    fn touch(&self, id: &SessionId, now: u32, expires_at: u32) -> Option<u32> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.live.get_mut(id)?;
+        let last_seen = session.0;
+        *session = (now, expires_at);
+        Some(last_seen)
+    }
+
+    fn remove(&self, id: &SessionId) {
+        self.sessions.lock().unwrap().live.remove(id);
+    }
+
+    fn prune(&self, now: u32) {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions
+            .live
+            .retain(|_, &mut (_, expires_at)| expires_at >= now);
+    }
+}
+
+pub struct Session {
+    pub id: SessionId,
+    pub data: Vec<u8>,
+    // Replacement token when the presented one is due for renewal.
+    pub renewed: Option<String>,
+}
+
+// Issues and validates session tokens.
+// Sessions expire after `idle_timeout` seconds without activity and
+// `absolute_timeout` seconds after they were created, whichever comes first.
+// Tokens older than `renew_after` seconds are re-issued on verification.
+pub struct SessionManager<S> {
+    branka: Branka,
+    store: S,
+    idle_timeout: u32,
+    absolute_timeout: u32,
+    renew_after: u32,
+}
+
+impl<S: SessionStore> SessionManager<S> {
+    pub fn new(branka: Branka, store: S, idle_timeout: u32, absolute_timeout: u32) -> Self {
+        SessionManager {
+            branka,
+            store,
+            idle_timeout,
+            absolute_timeout,
+            renew_after: idle_timeout / 2,
+        }
+    }
+
+    // Set the token age in seconds after which verification renews the token.
+    pub fn renew_after(mut self, renew_after: u32) -> Self {
+        self.renew_after = renew_after;
+        self
+    }
+
+    // Start a new session carrying `data`.
+    pub fn issue(&self, data: &[u8]) -> (SessionId, String) {
+        let mut id = [0u8; 16];
+        OsRng.fill_bytes(&mut id);
+        let now = self.branka.now();
+
+        self.store.insert(id, now, self.deadline(now, now));
+        (id, self.seal(&id, now, data))
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Session, BrankaError> {
+        let decoded = self.branka.decode_full(token)?;
+        let (issued, payload) = (decoded.timestamp, decoded.payload);
+        if payload.len() < SESSION_HEADER {
+            return Err(BrankaError::InvalidSession);
+        }
+
+        let mut id = [0u8; 16];
+        id.copy_from_slice(&payload[..16]);
+        let created = BigEndian::read_u32(&payload[16..SESSION_HEADER]);
+        let data = payload[SESSION_HEADER..].to_vec();
+
+        let now = self.branka.now();
+        let last_seen = self
+            .store
+            .touch(&id, now, self.deadline(created, now))
+            .ok_or(BrankaError::InvalidSession)?;
+        if now.saturating_sub(created) > self.absolute_timeout
+            || now.saturating_sub(last_seen) > self.idle_timeout
+        {
+            self.store.remove(&id);
+            return Err(BrankaError::Expired);
+        }
+
+        let renewed = if now.saturating_sub(issued) >= self.renew_after {
+            Some(self.seal(&id, created, &data))
+        } else {
+            None
+        };
+
+        Ok(Session { id, data, renewed })
+    }
+
+    // Terminate a session; its tokens are rejected from now on.
+    pub fn invalidate(&self, id: &SessionId) {
+        self.store.remove(id);
+    }
+
+    // Last second a session created at `created` and last seen at `now` can
+    // be verified.
+    fn deadline(&self, created: u32, now: u32) -> u32 {
+        created
+            .saturating_add(self.absolute_timeout)
+            .min(now.saturating_add(self.idle_timeout))
+    }
+
+    fn seal(&self, id: &SessionId, created: u32, data: &[u8]) -> String {
+        let mut payload = Vec::with_capacity(SESSION_HEADER + data.len());
+        payload.extend_from_slice(id);
+        payload.extend_from_slice(&created.to_be_bytes());
+        payload.extend_from_slice(data);
+        self.branka.encode(&payload)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Clock;
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    struct Now(AtomicU32);
+
+    impl Now {
+        fn advance(&self, secs: u32) {
+            self.0.fetch_add(secs, Ordering::SeqCst);
+        }
+    }
+
+    impl Clock for Now {
+        fn now(&self) -> u32 {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_session_issue_verify_invalidate() {
+        let manager = SessionManager::new(
+            Branka::new(&[3u8; 32], 0),
+            MemorySessionStore::new(),
+            1800,
+            86400,
+        );
+
+        let (id, token) = manager.issue(b"user:42");
+        let session = manager.verify(&token).unwrap();
+        assert_eq!(session.id, id);
+        assert_eq!(session.data, b"user:42");
+        assert!(session.renewed.is_none());
+
+        manager.invalidate(&id);
+        assert!(matches!(
+            manager.verify(&token),
+            Err(BrankaError::InvalidSession)
+        ));
+    }
+
+    #[test]
+    fn test_session_renewal() {
+        let manager = SessionManager::new(
+            Branka::new(&[3u8; 32], 0),
+            MemorySessionStore::new(),
+            1800,
+            86400,
+        )
+        .renew_after(0);
+
+        let (id, token) = manager.issue(b"user:42");
+        let renewed = manager.verify(&token).unwrap().renewed.unwrap();
+        let session = manager.verify(&renewed).unwrap();
+        assert_eq!(session.id, id);
+        assert_eq!(session.data, b"user:42");
+    }
+
+    #[test]
+    fn test_session_timeouts_and_pruning() {
+        let clock = Arc::new(Now(AtomicU32::new(1_000_000)));
+        let manager = SessionManager::new(
+            Branka::new(&[3u8; 32], 0).with_clock(clock.clone()),
+            MemorySessionStore::new(),
+            300,
+            600,
+        );
+
+        // Idle timeout, reset by each verification.
+        let (_, token) = manager.issue(b"user");
+        clock.advance(200);
+        assert!(manager.verify(&token).is_ok());
+        clock.advance(300);
+        assert!(manager.verify(&token).is_ok());
+        clock.advance(301);
+        assert_eq!(manager.verify(&token).err(), Some(BrankaError::Expired));
+
+        // Absolute timeout, despite continuous activity.
+        let (_, token) = manager.issue(b"user");
+        for _ in 0..3 {
+            clock.advance(200);
+            assert!(manager.verify(&token).is_ok());
+        }
+        clock.advance(1);
+        assert_eq!(manager.verify(&token).err(), Some(BrankaError::Expired));
+
+        // Abandoned sessions are dropped once past their deadline.
+        for _ in 0..MIN_PRUNE_AT {
+            manager.issue(b"abandoned");
+        }
+        clock.advance(301);
+        manager.issue(b"user");
+        assert_eq!(manager.store.len(), 1);
+        manager.store.prune(clock.now() + 301);
+        assert!(manager.store.is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{Branka, BrankaError};
+
+// Query parameter holding the token.
+const PARAM: &str = "signature";
+
+// A URL to sign: the method and request target it is valid for, an expiry
+// and optional caveats (restrictions the verifier hands back to the caller,
+// such as a client IP or a maximum upload size).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedUrl {
+    method: String,
+    target: String,
+    expires_at: u32,
+    caveats: Vec<(String, String)>,
+}
+
+impl SignedUrl {
+    // target: path and query string, e.g. `/files/report.pdf?v=2`.
+    pub fn new(method: &str, target: &str, expires_at: u32) -> SignedUrl {
+        SignedUrl {
+            method: String::from(method),
+            target: String::from(target),
+            expires_at,
+            caveats: Vec::new(),
+        }
+    }
+
+    // Panics if `name` or `value` is longer than 255 bytes.
+    pub fn caveat(mut self, name: &str, value: &str) -> Self {
+        assert!(
+            name.len() <= u8::MAX as usize && value.len() <= u8::MAX as usize,
+            "caveat too long"
+        );
+        self.caveats.push((String::from(name), String::from(value)));
+        self
+    }
+
+    pub fn expires_at(&self) -> u32 {
+        self.expires_at
+    }
+
+    pub fn caveats(&self) -> &[(String, String)] {
+        &self.caveats
+    }
+}
+
+// Signs and verifies URLs, like presigned S3 URLs. Method and target are
+// bound to the token as associated data rather than stored in it; expiry
+// and caveats are sealed in the token.
+pub struct UrlSigner {
+    branka: Branka,
+}
+
+impl UrlSigner {
+    // URLs carry their own expiry: `branka` usually has a TTL of 0.
+    pub fn new(branka: Branka) -> UrlSigner {
+        UrlSigner { branka }
+    }
+
+    // The signed target: `url`'s target with a `signature` parameter added.
+    pub fn sign(&self, url: &SignedUrl) -> Result<String, BrankaError> {
+        // Expiry || (Name length || Name || Value length || Value)*
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&url.expires_at.to_be_bytes());
+        for (name, value) in &url.caveats {
+            payload.push(name.len() as u8);
+            payload.extend_from_slice(name.as_bytes());
+            payload.push(value.len() as u8);
+            payload.extend_from_slice(value.as_bytes());
+        }
+        let token = self
+            .branka
+            .encode_aad(&payload, &bound(&url.method, &url.target))?;
+
+        let separator = if url.target.contains('?') { '&' } else { '?' };
+        let mut signed = String::with_capacity(url.target.len() + PARAM.len() + 2 + token.len());
+        signed.push_str(&url.target);
+        signed.push(separator);
+        signed.push_str(PARAM);
+        signed.push('=');
+        signed.push_str(&token);
+        Ok(signed)
+    }
+
+    // Verify an incoming request: its method and full request target, with
+    // the `signature` parameter last as `sign` put it. Returns the signed URL
+    // so the caller can check its caveats. Expired URLs fail with Expired.
+    pub fn verify(&self, method: &str, target: &str) -> Result<SignedUrl, BrankaError> {
+        let (unsigned, token) = split_signature(target).ok_or(BrankaError::InvalidData)?;
+        let payload = self.branka.decode_aad(token, &bound(method, unsigned))?;
+        if payload.len() < 4 {
+            return Err(BrankaError::InvalidPayload);
+        }
+
+        let mut url = SignedUrl::new(method, unsigned, BigEndian::read_u32(&payload));
+        let mut rest = &payload[4..];
+        while !rest.is_empty() {
+            let (name, tail) = field(rest)?;
+            let (value, tail) = field(tail)?;
+            url.caveats.push((name, value));
+            rest = tail;
+        }
+
+        if url.expires_at < self.branka.now() {
+            return Err(BrankaError::Expired);
+        }
+        Ok(url)
+    }
+}
+
+// Associated data binding a token to a request: Method || 0 || Target
+fn bound(method: &str, target: &str) -> Vec<u8> {
+    [method.as_bytes(), &[0], target.as_bytes()].concat()
+}
+
+// Split `target` into the unsigned target and the token of its trailing
+// `signature` parameter.
+fn split_signature(target: &str) -> Option<(&str, &str)> {
+    let at = target.rfind(PARAM)?;
+    let (unsigned, param) = (target.get(..at.checked_sub(1)?)?, &target[at..]);
+    if !matches!(target.as_bytes()[at - 1], b'?' | b'&') {
+        return None;
+    }
+    let token = param[PARAM.len()..].strip_prefix('=')?;
+    Some((unsigned, token))
+}
+
+fn field(bytes: &[u8]) -> Result<(String, &[u8]), BrankaError> {
+    let (&len, rest) = bytes.split_first().ok_or(BrankaError::InvalidPayload)?;
+    let (value, rest) = rest
+        .split_at_checked(usize::from(len))
+        .ok_or(BrankaError::InvalidPayload)?;
+    let value = String::from_utf8(value.to_vec()).map_err(|_| BrankaError::InvalidPayload)?;
+    Ok((value, rest))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_url() {
+        let signer = UrlSigner::new(Branka::new(&[1u8; 32], 0));
+        let url = SignedUrl::new("GET", "/files/a.pdf?v=2", u32::MAX).caveat("ip", "10.0.0.1");
+        let signed = signer.sign(&url).unwrap();
+        assert!(signed.starts_with("/files/a.pdf?v=2&signature="));
+
+        assert_eq!(signer.verify("GET", &signed).unwrap(), url);
+        assert_eq!(signer.verify("PUT", &signed), Err(BrankaError::InvalidData));
+        let moved = signed.replacen("a.pdf", "b.pdf", 1);
+        assert_eq!(signer.verify("GET", &moved), Err(BrankaError::InvalidData));
+        assert_eq!(
+            signer.verify("GET", "/files/a.pdf"),
+            Err(BrankaError::InvalidData)
+        );
+
+        let expired = signer.sign(&SignedUrl::new("GET", "/x", 1)).unwrap();
+        assert_eq!(signer.verify("GET", &expired), Err(BrankaError::Expired));
+    }
+}
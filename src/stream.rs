@@ -0,0 +1,392 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::{Branka, BrankaError};
+
+// Payload bytes carried by each chunk token by default. Base62 is quadratic
+// in the token length, so chunks stay small enough to decode quickly and to
+// fit under DEFAULT_MAX_TOKEN_LEN.
+pub const DEFAULT_STREAM_CHUNK_SIZE: usize = 4 * 1024;
+
+// Stream id || Chunk index || Last chunk flag
+pub(crate) const STREAM_HEADER: usize = 16 + 4 + 1;
+
+// Splits a reader into a sequence of chunk tokens.
+// Every chunk is a regular token whose payload starts with a header binding
+// it to its stream and position, so chunks can't be reordered, dropped,
+// mixed between streams or truncated without the decoder noticing.
+pub struct EncodeStream<'a, R> {
+    branka: &'a Branka,
+    chunks: Chunks<R>,
+    sealer: ChunkSealer,
+}
+
+impl<'a, R: Read> EncodeStream<'a, R> {
+    // Set the payload bytes carried by each chunk.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunks.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    fn next_chunk(&mut self) -> io::Result<Option<String>> {
+        let (data, last) = match self.chunks.next_chunk()? {
+            Some(chunk) => chunk,
+            None => return Ok(None),
+        };
+        let payload = self.sealer.payload(self.branka, &data, last)?;
+        self.branka.try_encode(&payload).map(Some).map_err(io_error)
+    }
+}
+
+// Cuts a reader into chunks, telling which one is the last.
+pub(crate) struct Chunks<R> {
+    reader: R,
+    chunk_size: usize,
+    pending: Option<Vec<u8>>,
+    done: bool,
+}
+
+impl<R: Read> Chunks<R> {
+    pub(crate) fn new(reader: R, chunk_size: usize) -> Self {
+        Chunks {
+            reader,
+            chunk_size,
+            pending: None,
+            done: false,
+        }
+    }
+
+    // The next chunk and whether it is the last one; an empty reader
+    // still yields one (empty, last) chunk.
+    pub(crate) fn next_chunk(&mut self) -> io::Result<Option<(Vec<u8>, bool)>> {
+        if self.done {
+            return Ok(None);
+        }
+        // Don't retry after an error.
+        self.done = true;
+
+        let data = match self.pending.take() {
+            Some(data) => data,
+            None => self.read_chunk()?,
+        };
+        // A full chunk is only the last one if the reader is exhausted.
+        let last = data.len() < self.chunk_size || {
+            let next = self.read_chunk()?;
+            let exhausted = next.is_empty();
+            self.pending = Some(next);
+            exhausted
+        };
+        self.done = last;
+        Ok(Some((data, last)))
+    }
+
+    fn read_chunk(&mut self) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(self.chunk_size);
+        (&mut self.reader)
+            .take(self.chunk_size as u64)
+            .read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+impl<R: Read> Iterator for EncodeStream<'_, R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        self.next_chunk().transpose()
+    }
+}
+
+// Builds the chunk payloads of one stream.
+#[derive(Default)]
+pub(crate) struct ChunkSealer {
+    id: Option<[u8; 16]>,
+    index: u32,
+}
+
+impl ChunkSealer {
+    pub(crate) fn payload(
+        &mut self,
+        branka: &Branka,
+        data: &[u8],
+        last: bool,
+    ) -> io::Result<Vec<u8>> {
+        let id = match self.id {
+            Some(id) => id,
+            None => {
+                let nonce = branka.next_nonce().map_err(io_error)?;
+                *self.id.insert(nonce[..16].try_into().unwrap())
+            }
+        };
+
+        let mut payload = Vec::with_capacity(STREAM_HEADER + data.len());
+        payload.extend_from_slice(&id);
+        payload.extend_from_slice(&self.index.to_be_bytes());
+        payload.push(last as u8);
+        payload.extend_from_slice(data);
+
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| io::Error::other("too many chunks"))?;
+        Ok(payload)
+    }
+}
+
+// Reassembles a stream from its chunk tokens, fed in order.
+pub struct StreamDecoder<'a> {
+    branka: &'a Branka,
+    id: Option<[u8; 16]>,
+    index: u32,
+    finished: bool,
+}
+
+impl StreamDecoder<'_> {
+    // Verify the next chunk token and return the data it carries.
+    pub fn push(&mut self, token: &str) -> Result<Vec<u8>, BrankaError> {
+        if self.finished {
+            return Err(BrankaError::InvalidStream);
+        }
+        let payload = self.branka.decode(token)?;
+        self.accept(payload)
+    }
+
+    // Check the chunk header of a decoded chunk payload and strip it.
+    pub(crate) fn accept(&mut self, mut payload: Vec<u8>) -> Result<Vec<u8>, BrankaError> {
+        if payload.len() < STREAM_HEADER || payload[20] > 1 {
+            return Err(BrankaError::InvalidStream);
+        }
+
+        let id: [u8; 16] = payload[..16].try_into().unwrap();
+        if *self.id.get_or_insert(id) != id || BigEndian::read_u32(&payload[16..20]) != self.index {
+            return Err(BrankaError::InvalidStream);
+        }
+        self.index += 1;
+        self.finished = payload[20] == 1;
+
+        payload.drain(..STREAM_HEADER);
+        Ok(payload)
+    }
+
+    // Whether the last chunk has been seen.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Check that the stream was complete.
+    pub fn finish(self) -> Result<(), BrankaError> {
+        if self.finished {
+            Ok(())
+        } else {
+            Err(BrankaError::InvalidStream)
+        }
+    }
+}
+
+// Encrypts everything written to it into chunk tokens, one per line.
+// Call finish() at the end: without the last chunk, readers reject the
+// stream as truncated.
+pub struct BrankaWriter<'a, W: Write> {
+    branka: &'a Branka,
+    inner: W,
+    chunk_size: usize,
+    sealer: ChunkSealer,
+    buf: Vec<u8>,
+}
+
+impl<'a, W: Write> BrankaWriter<'a, W> {
+    pub fn new(branka: &'a Branka, inner: W) -> Self {
+        BrankaWriter {
+            branka,
+            inner,
+            chunk_size: DEFAULT_STREAM_CHUNK_SIZE,
+            sealer: ChunkSealer::default(),
+            buf: Vec::new(),
+        }
+    }
+
+    // Set the payload bytes carried by each chunk.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    // Write the last chunk and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let data = core::mem::take(&mut self.buf);
+        self.write_chunk(&data, true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_chunk(&mut self, data: &[u8], last: bool) -> io::Result<()> {
+        let payload = self.sealer.payload(self.branka, data, last)?;
+        let token = self.branka.try_encode(&payload).map_err(io_error)?;
+        self.inner.write_all(token.as_bytes())?;
+        self.inner.write_all(b"\n")
+    }
+}
+
+impl<W: Write> Write for BrankaWriter<'_, W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        // Keep a full chunk back: it may turn out to be the last one.
+        while self.buf.len() > self.chunk_size {
+            let rest = self.buf.split_off(self.chunk_size);
+            let chunk = core::mem::replace(&mut self.buf, rest);
+            self.write_chunk(&chunk, false)?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Decrypts a stream written by BrankaWriter. Fails with InvalidData on a
+// tampered chunk and UnexpectedEof if the stream was truncated.
+pub struct BrankaReader<'a, R> {
+    inner: BufReader<R>,
+    decoder: StreamDecoder<'a>,
+    line: String,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl<'a, R: Read> BrankaReader<'a, R> {
+    pub fn new(branka: &'a Branka, inner: R) -> Self {
+        BrankaReader {
+            inner: BufReader::new(inner),
+            decoder: branka.stream_decoder(),
+            line: String::new(),
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for BrankaReader<'_, R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() && !self.decoder.is_finished() {
+            self.line.clear();
+            if self.inner.read_line(&mut self.line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "branka stream truncated",
+                ));
+            }
+            self.chunk = self
+                .decoder
+                .push(self.line.trim_end())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", err)))?;
+            self.pos = 0;
+        }
+
+        let n = out.len().min(self.chunk.len() - self.pos);
+        out[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+// BrankaError as an io::Error, for the std::io based APIs.
+pub(crate) fn io_error(err: BrankaError) -> io::Error {
+    io::Error::other(format!("branka: {:?}", err))
+}
+
+impl Branka {
+    // Encrypt everything `reader` yields as a sequence of chunk tokens of
+    // DEFAULT_STREAM_CHUNK_SIZE payload bytes, holding one chunk at a time.
+    pub fn encode_stream<R: Read>(&self, reader: R) -> EncodeStream<'_, R> {
+        EncodeStream {
+            branka: self,
+            chunks: Chunks::new(reader, DEFAULT_STREAM_CHUNK_SIZE),
+            sealer: ChunkSealer::default(),
+        }
+    }
+
+    pub fn stream_decoder(&self) -> StreamDecoder<'_> {
+        StreamDecoder {
+            branka: self,
+            id: None,
+            index: 0,
+            finished: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(branka: &Branka, tokens: &[String]) -> Result<Vec<u8>, BrankaError> {
+        let mut decoder = branka.stream_decoder();
+        let mut data = Vec::new();
+        for token in tokens {
+            data.extend(decoder.push(token)?);
+        }
+        decoder.finish().map(|_| data)
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let branka = Branka::new(&[7u8; 32], 0);
+        for len in [0usize, 1, 9, 10, 11, 35] {
+            let data: Vec<u8> = (0..len as u8).collect();
+            let tokens: Vec<String> = branka
+                .encode_stream(&data[..])
+                .chunk_size(10)
+                .collect::<io::Result<_>>()
+                .unwrap();
+            assert_eq!(tokens.len(), len.div_ceil(10).max(1));
+            assert_eq!(decode_all(&branka, &tokens).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_writer_reader() {
+        let branka = Branka::new(&[7u8; 32], 0);
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+
+        let mut writer = BrankaWriter::new(&branka, Vec::new()).chunk_size(64);
+        for part in data.chunks(100) {
+            writer.write_all(part).unwrap();
+        }
+        let stream = writer.finish().unwrap();
+
+        let mut decrypted = Vec::new();
+        BrankaReader::new(&branka, &stream[..])
+            .read_to_end(&mut decrypted)
+            .unwrap();
+        assert_eq!(decrypted, data);
+
+        let cut = stream.iter().position(|b| *b == b'\n').unwrap() + 1;
+        let err = BrankaReader::new(&branka, &stream[..cut])
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_stream_tampering() {
+        let branka = Branka::new(&[7u8; 32], 0);
+        let encode = |data: &[u8]| -> Vec<String> {
+            branka
+                .encode_stream(data)
+                .chunk_size(4)
+                .map(Result::unwrap)
+                .collect()
+        };
+        let tokens = encode(b"0123456789");
+        let other = encode(b"abcdefghij");
+
+        let truncated = &tokens[..2];
+        let reordered = [&tokens[1], &tokens[0], &tokens[2]].map(String::clone);
+        let mixed = [&tokens[0], &other[1], &tokens[2]].map(String::clone);
+        for tokens in [truncated, &reordered, &mixed] {
+            assert_eq!(decode_all(&branka, tokens), Err(BrankaError::InvalidStream));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// Helpers for testing code built on branka. Only available with the
+// `testing` feature.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use chacha20poly1305::XNonce;
+
+use crate::{Branka, Clock, VERSION};
+
+// A clock that only moves when told to, for testing expiry, idle timeouts
+// and skew without sleeping.
+#[derive(Debug)]
+pub struct MockClock {
+    now: AtomicU32,
+}
+
+impl MockClock {
+    pub fn new(now: u32) -> MockClock {
+        MockClock {
+            now: AtomicU32::new(now),
+        }
+    }
+
+    pub fn advance(&self, secs: u32) {
+        self.now.fetch_add(secs, Ordering::SeqCst);
+    }
+
+    pub fn set(&self, now: u32) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> u32 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+// A byte-exact expectation for the current token format.
+#[derive(Debug, Clone)]
+pub struct TestVector {
+    pub key: [u8; 32],
+    pub timestamp: u32,
+    pub nonce: [u8; 24],
+    pub payload: Vec<u8>,
+    pub token: String,
+}
+
+// Golden vectors covering empty, text and binary payloads and the
+// timestamp extremes, for other implementations and future versions of this
+// crate to check compatibility against.
+pub fn test_vectors() -> Vec<TestVector> {
+    let cases: [(u8, u32, u8, &[u8]); 6] = [
+        (0x00, 0, 0x00, b""),
+        (0x00, 123206400, 0x01, b"Hello world!"),
+        (0x42, 123206400, 0xBE, b"Hello world!"),
+        (0x73, 1_700_000_000, 0x5A, &[0u8; 8]),
+        (0xFF, u32::MAX, 0xFF, &[0xFFu8; 32]),
+        (0x0F, 1, 0x10, &[0x00, 0x01, 0x02, 0xFD, 0xFE, 0xFF]),
+    ];
+
+    cases
+        .iter()
+        .map(|&(key_byte, timestamp, nonce_byte, payload)| {
+            let key = [key_byte; 32];
+            let nonce = [nonce_byte; 24];
+            let clock = Arc::new(MockClock::new(timestamp));
+            let token = Branka::new_with_clock(&key, 0, clock).encode_with_nonce(
+                XNonce::from_slice(&nonce),
+                timestamp,
+                payload,
+                &[],
+            );
+            TestVector {
+                key,
+                timestamp,
+                nonce,
+                payload: payload.to_vec(),
+                token,
+            }
+        })
+        .collect()
+}
+
+// The golden vectors as a JSON document, binary fields hex encoded.
+pub fn test_vectors_json() -> String {
+    let mut json = String::new();
+    write!(json, "{{\n  \"version\": {},\n  \"vectors\": [", VERSION).unwrap();
+    for (i, vector) in test_vectors().iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write!(
+            json,
+            "\n    {{\"key\": \"{}\", \"timestamp\": {}, \"nonce\": \"{}\", \"payload\": \"{}\", \"token\": \"{}\"}}",
+            hex(&vector.key),
+            vector.timestamp,
+            hex(&vector.nonce),
+            hex(&vector.payload),
+            vector.token
+        )
+        .unwrap();
+    }
+    json.push_str("\n  ]\n}\n");
+    json
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Branka, BrankaError};
+
+    // Encoding vectors of the Branca specification (test_vectors.json):
+    // timestamp, payload, token; all with the key below and nonce 0xbeef....
+    const SPEC_KEY: &[u8; 32] = b"supersecretkeyyoushouldnotcommit";
+    const SPEC_VECTORS: [(u32, &[u8], &str); 8] = [
+        (
+            0,
+            b"Hello world!",
+            "870S4BYxgHw0KnP3W9fgVUHEhT5g86vJ17etaC5Kh5uIraWHCI1psNQGv298ZmjPwoYbjDQ9chy2z",
+        ),
+        (
+            u32::MAX,
+            b"Hello world!",
+            "89i7YCwu5tWAJNHUDdmIqhzOi5hVHOd4afjZcGMcVmM4enl4yeLiDyYv41eMkNmTX6IwYEFErCSqr",
+        ),
+        (
+            123206400,
+            b"Hello world!",
+            "875GH23U0Dr6nHFA63DhOyd9LkYudBkX8RsCTOMz5xoYAMw9sMd5QwcEqLDRnTDHPenOX7nP2trlT",
+        ),
+        (
+            0,
+            &[0; 8],
+            "1jIBheHbDdkCDFQmtgw4RUZeQoOJgGwTFJSpwOAk3XYpJJr52DEpILLmmwYl4tjdSbbNqcF1",
+        ),
+        (
+            u32::MAX,
+            &[0; 8],
+            "1jrx6DUu5q06oxykef2e2ZMyTcDRTQot9ZnwgifUtzAphGtjsxfbxXNhQyBEOGtpbkBgvIQx",
+        ),
+        (
+            123206400,
+            &[0; 8],
+            "1jJDJOEjuwVb9Csz1Ypw1KBWSkr0YDpeBeJN6NzJWx1VgPLmcBhu2SbkpQ9JjZ3nfUf7Aytp",
+        ),
+        (
+            0,
+            b"",
+            "4sfD0vPFhIif8cy4nB3BQkHeJqkOkDvinI4zIhMjYX4YXZU5WIq9ycCVjGzB5",
+        ),
+        (
+            123206400,
+            &[0x80],
+            "K9u6d0zjXp8RXNUGDyXAsB9AtPo60CD3xxQ2ulL8aQoTzXbvockRff0y1eXoHm",
+        ),
+    ];
+
+    // Tokens of test_vectors(), pinned so that format drift fails here
+    // rather than silently regenerating the vectors.
+    const PINNED_TOKENS: [&str; 6] = [
+        "4sfD0vOzeAfOta6HgLoOW4h5rhT53Gtbo0sAUh3rQzpKFHvicMkhVasGH9PPi",
+        "875GH233T5BTixorpBbrrpOkkh6LtOHegI3I1gVvBy87NdMBBnbwNFqquPowWzWYzR7ah5ZJDqNCy",
+        "875GH23TyZ41kN7CTDvlRlzUkOz6DYoE6UZp8ZBdjrp5E9SXFubnNMnffIiL91KSVtXGkwPH4pQjq",
+        "1jWLNKR8VromZPtjNxVhsbLmHSP1Ebk1qMhR7X3zaObjf3cpBlOz4eVBpjE5oc6MO1RRCmVq",
+        "4nwBdC5N7UVDSdIU7PDwBcuRGEMLo36o5PaHBeQDY5s6Piu6yhj4kApQGHYAvVXDIIXpC4E8b7Dxemkw3BYpPjIPxbUiXaa23cLClDxG",
+        "6IBKJKlRyhLIxexNb95PXv6su2UhzvtRRQ8HwMw94lW48CUGsli4ApUaM3ph3GApuhPab",
+    ];
+
+    #[test]
+    fn test_spec_vectors() {
+        let branka = Branka::new(SPEC_KEY, 0);
+        let nonce: Vec<u8> = [0xBE, 0xEF].repeat(12);
+        for (timestamp, payload, token) in SPEC_VECTORS {
+            assert_eq!(
+                branka.encode_with_nonce(XNonce::from_slice(&nonce), timestamp, payload, &[]),
+                token
+            );
+            let decoded = branka.decode_full(token).unwrap();
+            assert_eq!(decoded.timestamp, timestamp);
+            assert_eq!(decoded.payload, payload);
+        }
+    }
+
+    #[test]
+    fn test_vectors_decode() {
+        let vectors = test_vectors();
+        assert_eq!(vectors.len(), PINNED_TOKENS.len());
+        for (vector, token) in vectors.iter().zip(PINNED_TOKENS) {
+            assert_eq!(vector.token, token);
+            let decoded = Branka::new(&vector.key, 0).decode_full(token).unwrap();
+            assert_eq!(decoded.timestamp, vector.timestamp);
+            assert_eq!(decoded.nonce, vector.nonce);
+            assert_eq!(decoded.payload, vector.payload);
+        }
+
+        let json = test_vectors_json();
+        assert_eq!(json.matches("\"token\"").count(), PINNED_TOKENS.len());
+    }
+
+    #[test]
+    fn test_mock_clock_expiry() {
+        let clock = Arc::new(MockClock::new(1_000_000));
+        let branka = Branka::new(&[1u8; 32], 60).with_clock(clock.clone());
+        let token = branka.encode(b"data");
+
+        clock.advance(60);
+        assert!(branka.decode(&token).is_ok());
+        clock.advance(1);
+        assert_eq!(branka.decode(&token), Err(BrankaError::Expired));
+    }
+}
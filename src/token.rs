@@ -0,0 +1,131 @@
+use alloc::string::String;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+use crate::payload::type_tag;
+use crate::{Branka, BrankaError, Payload};
+
+// A token string tagged with the type of its payload, so the compiler
+// rejects decoding, say, a CSRF token where a session token is expected.
+pub struct Token<T> {
+    token: String,
+    payload: PhantomData<fn() -> T>,
+}
+
+impl<T> Token<T> {
+    // Treat `token` (received from a client, read from storage) as carrying
+    // a `T`; decode_struct still verifies it.
+    pub fn new(token: String) -> Token<T> {
+        Token {
+            token,
+            payload: PhantomData,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    pub fn into_string(self) -> String {
+        self.token
+    }
+}
+
+// Implemented by hand: none of these depend on T.
+impl<T> Clone for Token<T> {
+    fn clone(&self) -> Self {
+        Token::new(self.token.clone())
+    }
+}
+
+impl<T> PartialEq for Token<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.token == other.token
+    }
+}
+
+impl<T> Eq for Token<T> {}
+
+impl<T> Hash for Token<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.token.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for Token<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Token").field(&self.token).finish()
+    }
+}
+
+impl<T> fmt::Display for Token<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token)
+    }
+}
+
+impl Branka {
+    // Prefix struct payloads with the type tag of their type, and check it
+    // in decode_struct (WrongTokenKind on mismatch). Unlike Token<T>, this
+    // also catches confusion between services or processes.
+    pub fn with_type_tags(mut self) -> Branka {
+        self.type_tags = true;
+        self
+    }
+
+    pub fn encode_struct<T: Payload>(&self, value: &T) -> Token<T> {
+        let mut payload = value.to_payload();
+        if self.type_tags {
+            payload.splice(0..0, type_tag::<T>());
+        }
+        Token::new(self.encode(&payload))
+    }
+
+    pub fn decode_struct<T: Payload>(&self, token: &Token<T>) -> Result<T, BrankaError> {
+        self.decode_typed(token.as_str())
+    }
+
+    // decode_struct for a token string not (yet) wrapped in a Token<T>.
+    pub(crate) fn decode_typed<T: Payload>(&self, token: &str) -> Result<T, BrankaError> {
+        let payload = self.decode(token)?;
+        if !self.type_tags {
+            return T::from_payload(&payload);
+        }
+        match payload.split_first_chunk::<8>() {
+            Some((tag, rest)) if *tag == type_tag::<T>() => T::from_payload(rest),
+            _ => Err(BrankaError::WrongTokenKind),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_round_trip() {
+        let branka = Branka::new(&[5u8; 32], 0);
+        let token: Token<u64> = branka.encode_struct(&7);
+        assert_eq!(branka.decode_struct(&token), Ok(7));
+
+        let received = Token::<u64>::new(branka.encode(b"short"));
+        assert_eq!(
+            branka.decode_struct(&received),
+            Err(BrankaError::InvalidPayload)
+        );
+    }
+
+    #[test]
+    fn test_type_tags() {
+        let branka = Branka::new(&[5u8; 32], 0).with_type_tags();
+        let token = branka.encode_struct(&String::from("csrf"));
+        assert_eq!(branka.decode_struct(&token).unwrap(), "csrf");
+
+        let confused = Token::<Vec<u8>>::new(token.into_string());
+        assert_eq!(
+            branka.decode_struct(&confused),
+            Err(BrankaError::WrongTokenKind)
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::extensions::{Extensions, EXT_TRACE_CONTEXT};
+
+// W3C trace context (https://www.w3.org/TR/trace-context/) carried in a v2
+// token header, so work started from the token (an async job, a webhook
+// callback) joins the trace of the request that minted it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub flags: u8,
+}
+
+// Trace id || Span id || Flags
+const TRACE_CONTEXT_LEN: usize = 16 + 8 + 1;
+
+impl TraceContext {
+    // Parse a version 00 `traceparent` header value.
+    pub fn from_traceparent(header: &str) -> Option<TraceContext> {
+        let mut parts = header.trim().split('-');
+        if parts.next()? != "00" {
+            return None;
+        }
+        let mut context = TraceContext {
+            trace_id: [0; 16],
+            span_id: [0; 8],
+            flags: 0,
+        };
+        parse_hex(parts.next()?, &mut context.trace_id)?;
+        parse_hex(parts.next()?, &mut context.span_id)?;
+        let mut flags = [0u8; 1];
+        parse_hex(parts.next()?, &mut flags)?;
+        context.flags = flags[0];
+
+        let valid =
+            parts.next().is_none() && context.trace_id != [0; 16] && context.span_id != [0; 8];
+        valid.then_some(context)
+    }
+
+    // Format as a `traceparent` header value.
+    pub fn to_traceparent(&self) -> String {
+        let mut header = String::with_capacity(55);
+        header.push_str("00-");
+        push_hex(&mut header, &self.trace_id);
+        header.push('-');
+        push_hex(&mut header, &self.span_id);
+        header.push('-');
+        push_hex(&mut header, &[self.flags]);
+        header
+    }
+}
+
+impl Extensions {
+    pub fn with_trace_context(self, context: &TraceContext) -> Self {
+        let mut value = [0u8; TRACE_CONTEXT_LEN];
+        value[..16].copy_from_slice(&context.trace_id);
+        value[16..24].copy_from_slice(&context.span_id);
+        value[24] = context.flags;
+        self.with(EXT_TRACE_CONTEXT, &value)
+    }
+
+    pub fn trace_context(&self) -> Option<TraceContext> {
+        let value = self.get(EXT_TRACE_CONTEXT)?;
+        if value.len() != TRACE_CONTEXT_LEN {
+            return None;
+        }
+        Some(TraceContext {
+            trace_id: value[..16].try_into().unwrap(),
+            span_id: value[16..24].try_into().unwrap(),
+            flags: value[24],
+        })
+    }
+}
+
+// Lowercase hex only, as the spec requires.
+fn parse_hex(text: &str, out: &mut [u8]) -> Option<()> {
+    if text.len() != out.len() * 2 {
+        return None;
+    }
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        _ => None,
+    };
+    for (byte, pair) in out.iter_mut().zip(text.as_bytes().chunks(2)) {
+        *byte = digit(pair[0])? << 4 | digit(pair[1])?;
+    }
+    Some(())
+}
+
+fn push_hex(out: &mut String, bytes: &[u8]) {
+    for byte in bytes {
+        write!(out, "{:02x}", byte).unwrap();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Branka;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_trace_context_roundtrip() {
+        let context = TraceContext::from_traceparent(TRACEPARENT).unwrap();
+        assert_eq!(context.to_traceparent(), TRACEPARENT);
+        assert_eq!(context.flags, 1);
+
+        let branka = Branka::new(&[9u8; 32], 0);
+        let token = branka
+            .encode_v2(b"job", &Extensions::new().with_trace_context(&context))
+            .unwrap();
+        let (_, extensions) = branka.decode_v2(&token).unwrap();
+        assert_eq!(extensions.trace_context(), Some(context));
+    }
+
+    #[test]
+    fn test_trace_context_rejects_invalid() {
+        for header in [
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        ] {
+            assert_eq!(TraceContext::from_traceparent(header), None);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde_json::Value;
+
+use crate::{Branka, BrankaError, Payload};
+
+// Payload: the value as compact JSON.
+impl Payload for Value {
+    fn to_payload(&self) -> Vec<u8> {
+        // Serializing a Value only fails in the writer, and a Vec never does.
+        serde_json::to_vec(self).expect("JSON value serialization failed")
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, BrankaError> {
+        serde_json::from_slice(bytes).map_err(|_| BrankaError::InvalidPayload)
+    }
+}
+
+impl Branka {
+    // Encode claims the caller does not model as a Rust type, e.g. passed
+    // through by a gateway. The token is an ordinary encode of the JSON.
+    pub fn encode_value(&self, value: &Value) -> String {
+        self.encode(&value.to_payload())
+    }
+
+    // Decode a token whose payload is JSON. InvalidPayload if it isn't.
+    pub fn decode_value(&self, token: &str) -> Result<Value, BrankaError> {
+        Value::from_payload(&self.decode(token)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_value_round_trip() {
+        let branka = Branka::new(&[9u8; 32], 0);
+        let claims = json!({"sub": "user-1", "roles": ["admin"], "exp": 1700000000});
+        let token = branka.encode_value(&claims);
+        assert_eq!(branka.decode_value(&token), Ok(claims));
+
+        let token = branka.encode(b"{not json");
+        assert_eq!(
+            branka.decode_value(&token),
+            Err(BrankaError::InvalidPayload)
+        );
+    }
+}
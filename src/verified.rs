@@ -0,0 +1,66 @@
+use alloc::vec::Vec;
+
+use crate::{Branka, BrankaError, Payload, TokenId};
+
+// A token that passed verification: authenticated, decrypted and not
+// expired. Parsing the payload is left to the caller, who can try several
+// payload types without decoding the token again.
+#[derive(Debug, Clone)]
+pub struct VerifiedToken {
+    issued_at: u32,
+    id: TokenId,
+    payload: Vec<u8>,
+}
+
+impl VerifiedToken {
+    // Unix time the token was issued at.
+    pub fn issued_at(&self) -> u32 {
+        self.issued_at
+    }
+
+    pub fn id(&self) -> &TokenId {
+        &self.id
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
+    }
+
+    pub fn parse<T: Payload>(&self) -> Result<T, BrankaError> {
+        T::from_payload(&self.payload)
+    }
+}
+
+impl Branka {
+    // Verify a token without interpreting its payload.
+    pub fn verify(&self, data: &str) -> Result<VerifiedToken, BrankaError> {
+        let decoded = self.decode_full(data)?;
+        Ok(VerifiedToken {
+            issued_at: decoded.timestamp,
+            id: decoded.nonce,
+            payload: decoded.payload,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn test_verify_then_parse() {
+        let branka = Branka::new(&[5u8; 32], 0);
+        let verified = branka
+            .verify(&branka.encode(&u64::MAX.to_be_bytes()))
+            .unwrap();
+
+        assert_eq!(verified.parse::<String>(), Err(BrankaError::InvalidPayload));
+        assert_eq!(verified.parse::<u64>(), Ok(u64::MAX));
+        assert_eq!(verified.payload().len(), 8);
+    }
+}
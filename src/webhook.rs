@@ -0,0 +1,73 @@
+use alloc::string::String;
+
+use crate::{Branka, BrankaError, ReplayGuard, UsedTokenStore};
+
+// HTTP header carrying the signature.
+pub const WEBHOOK_HEADER: &str = "Branka-Signature";
+
+// Sender side of signed webhooks: the signature is a token with an empty
+// payload and the request body as associated data, so it authenticates the
+// exact body along with the time it was sent.
+pub struct WebhookSigner {
+    branka: Branka,
+}
+
+impl WebhookSigner {
+    pub fn new(branka: Branka) -> WebhookSigner {
+        WebhookSigner { branka }
+    }
+
+    // The WEBHOOK_HEADER value for a request with `body`.
+    pub fn sign(&self, body: &[u8]) -> Result<String, BrankaError> {
+        self.branka.encode_aad(&[], body)
+    }
+}
+
+// Receiver side: accepts each signature once, within `window` seconds of
+// being sent (see ReplayGuard).
+pub struct WebhookVerifier<S> {
+    guard: ReplayGuard<S>,
+}
+
+impl<S: UsedTokenStore> WebhookVerifier<S> {
+    pub fn new(branka: Branka, store: S, window: u32) -> Self {
+        WebhookVerifier {
+            guard: ReplayGuard::new(branka, store, window),
+        }
+    }
+
+    // Verify the WEBHOOK_HEADER value against the raw request body. A body
+    // changed in transit fails with InvalidData, a replayed request with
+    // TokenReused and a stale one with Expired.
+    pub fn verify(&self, signature: &str, body: &[u8]) -> Result<(), BrankaError> {
+        let payload = self.guard.decode_aad(signature, body)?;
+        if !payload.is_empty() {
+            return Err(BrankaError::WrongTokenKind);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::MemoryUsedTokenStore;
+
+    #[test]
+    fn test_webhook_signature() {
+        let key = [7u8; 32];
+        let signer = WebhookSigner::new(Branka::new(&key, 0));
+        let verifier = WebhookVerifier::new(Branka::new(&key, 0), MemoryUsedTokenStore::new(), 300);
+
+        let signature = signer.sign(b"{\"event\":\"paid\"}").unwrap();
+        assert_eq!(
+            verifier.verify(&signature, b"{\"event\":\"refunded\"}"),
+            Err(BrankaError::InvalidData)
+        );
+        assert_eq!(verifier.verify(&signature, b"{\"event\":\"paid\"}"), Ok(()));
+        assert_eq!(
+            verifier.verify(&signature, b"{\"event\":\"paid\"}"),
+            Err(BrankaError::TokenReused)
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{Branka, BrankaError};
+
+// Prefix of key-wrap payloads: keeps a wrapped key from being accepted as
+// any other token minted under the same key-encryption key, and the reverse.
+const KEY_WRAP_PURPOSE: &[u8] = b"branka:key-wrap:v1\0";
+
+impl Branka {
+    // Seal a data-encryption key under this instance's key (the
+    // key-encryption key), for storage or transport as a token.
+    pub fn wrap_key(&self, key: &[u8]) -> String {
+        let mut payload = Vec::with_capacity(KEY_WRAP_PURPOSE.len() + key.len());
+        payload.extend_from_slice(KEY_WRAP_PURPOSE);
+        payload.extend_from_slice(key);
+        let token = self.encode(&payload);
+        payload.zeroize();
+        token
+    }
+
+    // Recover a key sealed by wrap_key. Wrapped keys are subject to this
+    // instance's TTL like any token; use a TTL of 0 for keys kept at rest.
+    // The key is wiped when the returned buffer is dropped.
+    pub fn unwrap_key(&self, token: &str) -> Result<Zeroizing<Vec<u8>>, BrankaError> {
+        let mut payload = Zeroizing::new(self.decode(token)?);
+        if !payload.starts_with(KEY_WRAP_PURPOSE) {
+            return Err(BrankaError::WrongTokenKind);
+        }
+        // In place, so the key never leaves the wiped buffer.
+        payload.drain(..KEY_WRAP_PURPOSE.len());
+        Ok(payload)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_key() {
+        let kek = Branka::new(&[2u8; 32], 0);
+        let wrapped = kek.wrap_key(&[7u8; 32]);
+        assert_eq!(*kek.unwrap_key(&wrapped).unwrap(), [7u8; 32]);
+
+        let plain = kek.encode(&[7u8; 32]);
+        assert_eq!(kek.unwrap_key(&plain), Err(BrankaError::WrongTokenKind));
+    }
+}
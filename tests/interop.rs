@@ -0,0 +1,69 @@
+// Round trips tokens between branka and the reference `branca` crate to
+// catch silent format drift.
+//
+// Branka::new reads the system clock, so these need `std`.
+#![cfg(feature = "std")]
+
+extern crate branca;
+extern crate branka;
+extern crate getrandom;
+
+use branka::Branka;
+
+static PAYLOAD_SIZES: [usize; 6] = [0, 1, 15, 16, 255, 4096];
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    getrandom::getrandom(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_branka_to_branca() {
+    let key = random_bytes(32);
+    let branka = Branka::new(&key, 3000).with_max_token_len(16 * 1024);
+    let branca = branca::Branca::new(&key).unwrap();
+
+    for size in PAYLOAD_SIZES.iter() {
+        let payload = random_bytes(*size);
+        let token = branka.encode(&payload);
+        assert_eq!(branca.decode(&token, 3000).unwrap(), payload);
+    }
+}
+
+#[test]
+fn test_branca_to_branka() {
+    let key = random_bytes(32);
+    let branka = Branka::new(&key, 3000).with_max_token_len(16 * 1024);
+    let mut branca = branca::Branca::new(&key).unwrap();
+
+    for size in PAYLOAD_SIZES.iter() {
+        let payload = random_bytes(*size);
+        let token = branca.encode(&payload).unwrap();
+        assert_eq!(branka.decode(&token).unwrap(), payload);
+    }
+}
+
+#[test]
+fn test_tampered_tokens_rejected_by_both() {
+    let key = random_bytes(32);
+    let branka = Branka::new(&key, 3000);
+    let branca = branca::Branca::new(&key).unwrap();
+
+    let mut token = branka.encode(b"interop").into_bytes();
+    let last = token.len() - 1;
+    token[last] = if token[last] == b'0' { b'1' } else { b'0' };
+    let token = String::from_utf8(token).unwrap();
+
+    assert!(branka.decode(&token).is_err());
+    assert!(branca.decode(&token, 3000).is_err());
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_golden_vectors_decode_with_branca() {
+    for vector in branka::testing::test_vectors() {
+        let branca = branca::Branca::new(&vector.key).unwrap();
+        assert_eq!(branca.decode(&vector.token, 0).unwrap(), vector.payload);
+    }
+}